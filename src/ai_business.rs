@@ -0,0 +1,313 @@
+pub struct AiBusinessSystems;
+use crate::*;
+use crate::building::{spawn_building, BuildingKind, BuildingPlaced, Footprint};
+use crate::finance::{Solvency, SolvencyStage};
+use crate::logistics::DropOff;
+use crate::market::{Market, MarketOrders, TradePolicy};
+use crate::notifications::Notification;
+use crate::ownership::{Factions, Holdings, Owner};
+use crate::production::{spawn_workstations, Bussiness, Employees, Good, Recipe, Storage, Workstation};
+use crate::zoning::{find_zoned_site, ZoneRules};
+use bevy::prelude::*;
+use bevy::ecs::schedule::common_conditions::on_event;
+
+impl Plugin for AiBusinessSystems {
+    fn build(&self, app: &mut App) {
+        app
+            .insert_resource(AiConfig::default())
+            .insert_resource(AiTicks(0))
+            .init_resource::<PendingCompetitors>()
+            .add_ai_competitor("Steady Provisions", CautiousStrategy)
+            .add_ai_competitor("Hungry Wolf Ltd", AggressiveStrategy)
+            .add_systems(Startup, spawn_ai_competitors)
+            .add_systems(Update, run_ai_decisions.run_if(on_event::<WorldTick>));
+    }
+}
+
+pub trait AiAppExt {
+    // Queues a competitor business run by `strategy`, spawned on startup
+    fn add_ai_competitor(&mut self, name: &str, strategy: impl AiStrategy) -> &mut Self;
+}
+
+impl AiAppExt for App {
+    fn add_ai_competitor(&mut self, name: &str, strategy: impl AiStrategy) -> &mut Self {
+        self.world_mut()
+            .get_resource_or_init::<PendingCompetitors>()
+            .0
+            .push((name.to_string(), Box::new(strategy)));
+        self
+    }
+}
+
+#[derive(Resource, Debug, Clone)]
+pub struct AiConfig {
+    // AI owners act every this many ticks
+    pub decision_interval: u32,
+    pub starting_money: i32,
+    pub building_cost: i32,
+    // Random sites tried when looking for free grid space in a fitting zone
+    pub site_attempts: u32,
+    pub recipes: Vec<Recipe>,
+}
+
+impl Default for AiConfig {
+    fn default() -> Self {
+        Self {
+            decision_interval: 10,
+            starting_money: 1500,
+            building_cost: 500,
+            site_attempts: 32,
+            recipes: vec![
+                Recipe { output: Good::Food, total_work: 20.0, inputs: vec![(Good::Grain, 1)], worker_slots: 3 },
+                Recipe { output: Good::Grain, total_work: 20.0, inputs: Vec::new(), worker_slots: 2 },
+                Recipe { output: Good::Wood, total_work: 30.0, inputs: Vec::new(), worker_slots: 2 },
+            ],
+        }
+    }
+}
+
+#[derive(Resource)]
+struct AiTicks(u32);
+
+#[derive(Resource, Default)]
+struct PendingCompetitors(Vec<(String, Box<dyn AiStrategy>)>);
+
+// What an AI owner sees of its own business when deciding
+pub struct BusinessView<'a> {
+    pub storage: &'a Storage,
+    pub workstations: usize,
+    pub workers: usize,
+    pub worker_slots: u32,
+    pub solvency: SolvencyStage,
+    pub building_cost: i32,
+    pub market: &'a Market,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AiDecision {
+    Hold,
+    // Build a new workshop producing this good
+    Expand(Good),
+    // Close the least useful workstation
+    Contract,
+}
+
+pub trait AiStrategy: Send + Sync + 'static {
+    fn name(&self) -> &'static str;
+    // Lowest price, relative to the market price, the business sells its surplus at
+    fn sell_margin(&self) -> f32;
+    fn decide(&self, view: &BusinessView, recipes: &[Recipe]) -> AiDecision;
+}
+
+// Recipe with the best market value per unit of work, net of input costs
+pub fn most_profitable<'a>(market: &Market, recipes: &'a [Recipe]) -> Option<&'a Recipe> {
+    let value = |r: &Recipe| {
+        let input_cost: f32 = r.inputs.iter().map(|(good, n)| market.price(*good) * *n as f32).sum();
+        (market.price(r.output) - input_cost) / r.total_work
+    };
+    recipes.iter().max_by(|a, b| value(a).total_cmp(&value(b)))
+}
+
+// Expands only when fully staffed with a large cash buffer, cuts back at the first sign of trouble
+pub struct CautiousStrategy;
+
+impl AiStrategy for CautiousStrategy {
+    fn name(&self) -> &'static str { "Cautious" }
+
+    fn sell_margin(&self) -> f32 { 0.95 }
+
+    fn decide(&self, view: &BusinessView, recipes: &[Recipe]) -> AiDecision {
+        if view.solvency != SolvencyStage::Healthy && view.workstations > 1 {
+            return AiDecision::Contract;
+        }
+        let staffed = view.workers as u32 >= view.worker_slots;
+        if view.workstations == 0 || (staffed && view.storage.money >= view.building_cost * 3) {
+            if let Some(recipe) = most_profitable(view.market, recipes) {
+                return AiDecision::Expand(recipe.output);
+            }
+        }
+        AiDecision::Hold
+    }
+}
+
+// Expands whenever it can pay for a building and undercuts the market, only contracts once shut down
+pub struct AggressiveStrategy;
+
+impl AiStrategy for AggressiveStrategy {
+    fn name(&self) -> &'static str { "Aggressive" }
+
+    fn sell_margin(&self) -> f32 { 0.8 }
+
+    fn decide(&self, view: &BusinessView, recipes: &[Recipe]) -> AiDecision {
+        if view.solvency == SolvencyStage::ShutDown && view.workstations > 1 {
+            return AiDecision::Contract;
+        }
+        if view.storage.money >= view.building_cost {
+            if let Some(recipe) = most_profitable(view.market, recipes) {
+                return AiDecision::Expand(recipe.output);
+            }
+        }
+        AiDecision::Hold
+    }
+}
+
+#[derive(Component)]
+pub struct AiOwner {
+    pub strategy: Box<dyn AiStrategy>,
+}
+
+fn spawn_ai_competitors(
+    mut commands: Commands,
+    config: Res<AiConfig>,
+    factions: Res<Factions>,
+    mut pending: ResMut<PendingCompetitors>,
+    mut notifications: EventWriter<Notification>,
+) {
+    for (name, strategy) in pending.0.drain(..) {
+        let policy = TradePolicy { sell_margin: strategy.sell_margin(), buy_margin: 1.0, ..default() };
+        notifications.write(Notification::info("AI", format!("{} joins the market ({})", name, strategy.name())));
+        commands.spawn((
+            EntityLabel(name),
+            Bussiness,
+            AiOwner { strategy },
+            Owner(factions.world),
+            Storage::with_money(config.starting_money),
+            MarketOrders::default(),
+            policy,
+        ));
+    }
+}
+
+fn run_ai_decisions(
+    mut commands: Commands,
+    mut ticks: ResMut<AiTicks>,
+    config: Res<AiConfig>,
+    market: Res<Market>,
+    mut grid: ResMut<WorldGrid>,
+    zone_rules: Res<ZoneRules>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    common_materials: Res<CommonMaterials>,
+    mut placed: EventWriter<BuildingPlaced>,
+    mut businesses: Query<(Entity, &AiOwner, &mut Storage, &mut TradePolicy, Option<&Employees>, Option<&Solvency>, Option<&Holdings>), With<Bussiness>>,
+    workstations: Query<&Workstation>,
+) {
+    ticks.0 += 1;
+    if ticks.0 % config.decision_interval.max(1) != 0 { return; }
+
+    for (business, owner, mut storage, mut policy, employees, solvency, holdings) in &mut businesses {
+        let own: Vec<(Entity, &Workstation)> = holdings
+            .into_iter()
+            .flat_map(|h| h.iter())
+            .filter_map(|e| workstations.get(e).ok().map(|ws| (e, ws)))
+            .collect();
+        let view = BusinessView {
+            storage: &storage,
+            workstations: own.len(),
+            workers: employees.map_or(0, |e| e.count()),
+            worker_slots: own.iter().map(|(_, ws)| ws.worker_slots).sum(),
+            solvency: solvency.map_or(SolvencyStage::Healthy, |s| s.stage),
+            building_cost: config.building_cost,
+            market: &market,
+        };
+
+        match owner.strategy.decide(&view, &config.recipes) {
+            AiDecision::Hold => {}
+            AiDecision::Expand(good) => {
+                if storage.money < config.building_cost { continue; }
+                let Some(recipe) = config.recipes.iter().find(|r| r.output == good) else { continue; };
+                let kind = BuildingKind::Workshop;
+                let size = kind.size();
+                let Some(origin) = find_zoned_site(&grid, &zone_rules, kind, config.site_attempts) else { continue; };
+
+                let pos = grid.grid_to_world(origin, size);
+                let building = spawn_building(&mut commands, &mut meshes, &grid, common_materials.building.clone(), size, pos);
+                // The workshop doubles as the business warehouse for its own output
+                commands.entity(building).insert((kind, DropOff { storage: business }, Footprint { origin, size }, Owner(business)));
+                grid.modify_rectangle(origin, size);
+                spawn_workstations(&mut commands, &grid, building, business, kind, size, Some(recipe));
+                placed.write(BuildingPlaced { entity: building, kind, origin, size });
+                storage.money -= config.building_cost;
+                policy.sell_above.insert(good, 0);
+            }
+            AiDecision::Contract => {
+                if let Some((workstation, _)) = own.iter().min_by_key(|(_, ws)| ws.worker_slots) {
+                    commands.entity(*workstation).despawn();
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::market::MarketConfig;
+
+    // A single fully staffed workshop; default prices make food the most profitable recipe
+    fn view<'a>(storage: &'a Storage, market: &'a Market) -> BusinessView<'a> {
+        BusinessView {
+            storage,
+            workstations: 1,
+            workers: 3,
+            worker_slots: 3,
+            solvency: SolvencyStage::Healthy,
+            building_cost: 500,
+            market,
+        }
+    }
+
+    #[test]
+    fn cautious_expands_only_when_staffed_with_a_large_buffer() {
+        let market = Market::new(&MarketConfig::default());
+        let recipes = AiConfig::default().recipes;
+        let rich = Storage::with_money(1500);
+        let poorer = Storage::with_money(1499);
+
+        assert_eq!(CautiousStrategy.decide(&view(&rich, &market), &recipes), AiDecision::Expand(Good::Food));
+        assert_eq!(CautiousStrategy.decide(&view(&poorer, &market), &recipes), AiDecision::Hold);
+        let understaffed = BusinessView { workers: 2, ..view(&rich, &market) };
+        assert_eq!(CautiousStrategy.decide(&understaffed, &recipes), AiDecision::Hold);
+    }
+
+    #[test]
+    fn cautious_always_opens_its_first_workshop() {
+        let market = Market::new(&MarketConfig::default());
+        let broke = Storage::with_money(0);
+        let empty = BusinessView { workstations: 0, workers: 0, worker_slots: 0, ..view(&broke, &market) };
+        assert_eq!(CautiousStrategy.decide(&empty, &AiConfig::default().recipes), AiDecision::Expand(Good::Food));
+    }
+
+    #[test]
+    fn cautious_contracts_at_the_first_warning() {
+        let market = Market::new(&MarketConfig::default());
+        let recipes = AiConfig::default().recipes;
+        let storage = Storage::with_money(-10);
+        let warned = BusinessView { workstations: 2, solvency: SolvencyStage::Warning, ..view(&storage, &market) };
+        assert_eq!(CautiousStrategy.decide(&warned, &recipes), AiDecision::Contract);
+        // Never gives up its last workstation
+        let last = BusinessView { workstations: 1, ..warned };
+        assert_eq!(CautiousStrategy.decide(&last, &recipes), AiDecision::Hold);
+    }
+
+    #[test]
+    fn aggressive_expands_whenever_it_can_pay() {
+        let market = Market::new(&MarketConfig::default());
+        let recipes = AiConfig::default().recipes;
+        let affordable = Storage::with_money(500);
+        let short = Storage::with_money(499);
+
+        let understaffed = BusinessView { workers: 0, ..view(&affordable, &market) };
+        assert_eq!(AggressiveStrategy.decide(&understaffed, &recipes), AiDecision::Expand(Good::Food));
+        assert_eq!(AggressiveStrategy.decide(&view(&short, &market), &recipes), AiDecision::Hold);
+        let warned = BusinessView { workstations: 2, solvency: SolvencyStage::Warning, ..view(&affordable, &market) };
+        assert_eq!(AggressiveStrategy.decide(&warned, &recipes), AiDecision::Expand(Good::Food));
+    }
+
+    #[test]
+    fn aggressive_contracts_only_once_shut_down() {
+        let market = Market::new(&MarketConfig::default());
+        let storage = Storage::with_money(-10);
+        let shut = BusinessView { workstations: 2, solvency: SolvencyStage::ShutDown, ..view(&storage, &market) };
+        assert_eq!(AggressiveStrategy.decide(&shut, &AiConfig::default().recipes), AiDecision::Contract);
+    }
+}
@@ -1,4 +1,11 @@
 use crate::*;
+use crate::ownership::{ActiveBusiness, Factions, Owner};
+use crate::jobs::{JobBoard, JobId};
+use crate::production::{spawn_workstations, Bussiness, PlayerOwned};
+use crate::farming::{ActiveCrop, CropDefinitions};
+use crate::notifications::Notification;
+use crate::world_grid::Zone;
+use crate::zoning::ZoneRules;
 
 
 #[derive(Event)]
@@ -7,11 +14,77 @@ struct RequestSpawnBuildingTemplate {
     pos: Vec2,
 }
 
+// Buildings the player can pick from the building menu
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum BuildingKind {
+    #[default]
+    House,
+    Workshop,
+    // Storage buildings that slow spoilage of their owner's stock
+    Cellar,
+    ColdStore,
+}
+
+impl BuildingKind {
+    // Footprint in grid cells
+    pub fn size(&self) -> Vec2 {
+        match self {
+            BuildingKind::House => Vec2::new(4.0, 2.0),
+            BuildingKind::Workshop => Vec2::new(4.0, 5.0),
+            BuildingKind::Cellar => Vec2::new(3.0, 3.0),
+            BuildingKind::ColdStore => Vec2::new(3.0, 2.0),
+        }
+    }
+}
+
+// A placed building still waiting for builders. Removed once its build job completes.
+#[derive(Component, Debug, Clone, Copy)]
+pub struct ConstructionSite {
+    pub work_ticks: u32,
+}
+
+impl ConstructionSite {
+    // Two ticks of work per grid cell
+    pub fn for_size(size: Vec2) -> Self {
+        Self { work_ticks: (size.x * size.y).round() as u32 * 2 }
+    }
+}
+
+// Grid cells claimed by a placed building, freed again when it is demolished
+#[derive(Component, Debug, Clone, Copy)]
+pub struct Footprint {
+    pub origin: Vec2,
+    pub size: Vec2,
+}
+
+// Written when a building template is confirmed and its tiles are claimed on the grid
+#[derive(Event, Debug, Clone, Copy)]
+pub struct BuildingPlaced {
+    pub entity: Entity,
+    pub kind: BuildingKind,
+    pub origin: Vec2,
+    pub size: Vec2,
+}
+
+// What a left click does in the building state
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BuildTool {
+    // Place the current building template
+    #[default]
+    Place,
+    // Paint farm plots by dragging
+    Farm,
+    // Drag out a rectangle of this zone
+    Zone(Zone),
+}
+
 #[derive(Resource)]
 pub struct BuildingControlState{
     pub cur_cel: Vec2,
     pub cur_building: Option<Entity>,
     pub cur_size: Vec2,
+    pub cur_kind: BuildingKind,
+    pub tool: BuildTool,
     pub overlaps: HashSet<Entity>,
 }
 
@@ -19,14 +92,17 @@ impl Plugin for GameBuildingPlugins {
     fn build(&self, app: &mut App) {
         app
         .add_event::<RequestSpawnBuildingTemplate>()
+        .add_event::<BuildingPlaced>()
         .insert_resource(BuildingControlState {
             cur_cel: Vec2::default(),
             cur_building: None,
             overlaps: HashSet::default(),
-            cur_size: Vec2::default()
+            cur_size: Vec2::default(),
+            cur_kind: BuildingKind::default(),
+            tool: BuildTool::default(),
         })
         .add_systems(Update,
-             (select_building, handle_building_collisions, game_state_control_building, building_prototype, create_building_template)
+             (select_building, handle_building_collisions, game_state_control_building, building_prototype, create_building_template, rotate_building_template, demolish_building)
             .run_if(in_state(GameControlState::Building)))
         .add_systems(OnExit(GameControlState::Building), state_cleanup_building)
         .add_systems(OnEnter(GameControlState::Building), state_ui_startup_building);
@@ -35,12 +111,18 @@ impl Plugin for GameBuildingPlugins {
 
 fn building_prototype(
     mut state: ResMut<BuildingControlState>,
-    m_buttons: Res<ButtonInput<MouseButton>>,
+    actions: Res<Actions>,
     mut grid: ResMut<WorldGrid>,
     common_materials: Res<CommonMaterials>,
     mut query: Query<&mut Transform>,
     mut material_query: Query<&mut MeshMaterial2d<ColorMaterial>>,
-    over_ui: Res<UiBlockHoverCount>
+    over_ui: Res<UiBlockHoverCount>,
+    mut commands: Commands,
+    mut placed_ev: EventWriter<BuildingPlaced>,
+    active: Res<ActiveBusiness>,
+    factions: Res<Factions>,
+    zone_rules: Res<ZoneRules>,
+    mut notifications: EventWriter<Notification>,
 ){
     // this function will eventually be stripped out because none of its behaviour is desired
     let origin = state.cur_cel;
@@ -50,17 +132,93 @@ fn building_prototype(
             transform.translation = vec3(pos.x, pos.y, 0.0);
         }
         
-        if m_buttons.just_pressed(MouseButton::Left) && state.overlaps.is_empty() && over_ui.0 <= 0 {
-            //also triggers when trying to drag camera. 
+        if actions.just_pressed(Action::Place) && state.overlaps.is_empty() && over_ui.0 <= 0 {
+            if !zone_rules.allows_placement(&grid, origin, state.cur_size, state.cur_kind) {
+                notifications.write(Notification::warning("Building", format!("{:?} is not allowed in this zone", state.cur_kind)));
+                return;
+            }
+            // Water, wells and fields stay as they are
+            if !grid.is_area_free(origin, state.cur_size) {
+                notifications.write(Notification::warning("Building", format!("{:?} needs clear ground", state.cur_kind)));
+                return;
+            }
             if let Ok(mut material) = material_query.get_mut(building){
                 material.0 = common_materials.building.clone();
                 state.cur_building = None;
                 grid.modify_rectangle(origin, state.cur_size);
+                // Owned by the player's active business, or the player directly if they run none
+                let owner = active.0.unwrap_or(factions.player);
+                commands.entity(building).insert((
+                    state.cur_kind,
+                    ConstructionSite::for_size(state.cur_size),
+                    Footprint { origin, size: state.cur_size },
+                    Owner(owner),
+                ));
+                spawn_workstations(&mut commands, &grid, building, owner, state.cur_kind, state.cur_size, None);
+                placed_ev.write(BuildingPlaced { entity: building, kind: state.cur_kind, origin, size: state.cur_size });
             }
         }
     }
 }
 
+// Demolish removes the placed building under the cursor, its workstations go with it as children
+fn demolish_building(
+    actions: Res<Actions>,
+    state: Res<BuildingControlState>,
+    factions: Res<Factions>,
+    mut events: EventReader<CursorWorldEvent>,
+    mut grid: ResMut<WorldGrid>,
+    mut board: ResMut<JobBoard>,
+    mut commands: Commands,
+    buildings: Query<(Entity, &Transform, &BuildingKind, &Footprint, &Owner, Option<&Children>), With<Building>>,
+    player_businesses: Query<(), (With<Bussiness>, With<PlayerOwned>)>,
+    mut notifications: EventWriter<Notification>,
+) {
+    let Some(cursor) = events.read().last().copied() else { return; };
+    if !actions.just_pressed(Action::Demolish) { return; }
+
+    // The footprint rather than the kind's size, as the building may have been rotated
+    let hit = buildings.iter().find(|(entity, t, _, footprint, owner, _)| {
+        let half = footprint.size * grid.scale() as f32 / 2.0;
+        let offset = (cursor.world - t.translation.truncate()).abs();
+        let players = owner.0 == factions.player || player_businesses.contains(owner.0);
+        Some(*entity) != state.cur_building && players && offset.x <= half.x && offset.y <= half.y
+    });
+    if let Some((entity, transform, kind, footprint, _, children)) = hit {
+        // Jobs already claimed at the building or its workstations would outlive their poster;
+        // released, they get dropped as orphans and their agents see them cancelled
+        let posters: Vec<Entity> = std::iter::once(entity).chain(children.map_or(&[][..], |c| &c[..]).iter().copied()).collect();
+        let claimed: Vec<JobId> = board
+            .iter()
+            .filter(|(_, job)| job.claimed_by.is_some() && posters.contains(&job.poster))
+            .map(|(id, _)| id)
+            .collect();
+        for id in claimed {
+            board.release(id);
+        }
+        grid.clear_rectangle(footprint.origin, footprint.size);
+        commands.entity(entity).despawn();
+        notifications.write(Notification::info("Building", format!("Demolished {:?}", kind)).at(transform.translation.truncate()));
+    }
+}
+
+// Rotate turns the template a quarter, swapping the footprint's sides
+fn rotate_building_template(
+    mut commands: Commands,
+    actions: Res<Actions>,
+    grid: Res<WorldGrid>,
+    mut state: ResMut<BuildingControlState>,
+    mut spawn_ev: EventWriter<RequestSpawnBuildingTemplate>,
+) {
+    if !actions.just_pressed(Action::Rotate) { return; }
+    let Some(template) = state.cur_building.take() else { return; };
+    commands.entity(template).despawn();
+    state.overlaps.clear();
+    state.cur_size = Vec2::new(state.cur_size.y, state.cur_size.x);
+    let pos = grid.grid_to_world(state.cur_cel, state.cur_size);
+    spawn_ev.write(RequestSpawnBuildingTemplate { size: state.cur_size, pos });
+}
+
 fn create_building_template (
     mut events: EventReader<RequestSpawnBuildingTemplate>,
     grid: Res<WorldGrid>,
@@ -70,30 +228,41 @@ fn create_building_template (
     mut state: ResMut<BuildingControlState>
 ) {
     for ev in events.read() {
-        let mesh_handle = meshes.add(Mesh::from(
-                Rectangle::new(ev.size.x * grid.scale() as f32,
-                    ev.size.y * grid.scale() as f32)));
-        let material_handle = common_materials.green_half.clone();
-        let visual = VisualBundle{
-            mesh: Mesh2d(mesh_handle.clone()),
-            material: MeshMaterial2d(material_handle.clone()),
-            transform: Transform::from_xyz(ev.pos.x, ev.pos.y, 0.0)
-        };
-        let collision = CollisionBundle::rect_sensor(
-            (ev.size - 0.01 )* grid.scale() as f32, RigidBody::Fixed, true);
-        let ent = commands.spawn(BuildingBundle { visual, collision, building: Building });
-        state.cur_building = Some(ent.id());
+        let ent = spawn_building(&mut commands, &mut meshes, &grid, common_materials.green_half.clone(), ev.size, ev.pos);
+        state.cur_building = Some(ent);
     }
     events.clear();
 }
 
+// Spawns the building body (mesh and sensor collider) of `size` grid cells at world position `pos`
+pub fn spawn_building(
+    commands: &mut Commands,
+    meshes: &mut Assets<Mesh>,
+    grid: &WorldGrid,
+    material: Handle<ColorMaterial>,
+    size: Vec2,
+    pos: Vec2,
+) -> Entity {
+    let mesh_handle = meshes.add(Mesh::from(
+            Rectangle::new(size.x * grid.scale() as f32,
+                size.y * grid.scale() as f32)));
+    let visual = VisualBundle{
+        mesh: Mesh2d(mesh_handle),
+        material: MeshMaterial2d(material),
+        transform: Transform::from_xyz(pos.x, pos.y, 0.0)
+    };
+    let collision = CollisionBundle::rect_sensor(
+        (size - 0.01 )* grid.scale() as f32, RigidBody::Fixed, true);
+    commands.spawn(BuildingBundle { visual, collision, building: Building }).id()
+}
+
 
 fn select_building(
     rapier_context: ReadRapierContext,
-    m_buttons: Res<ButtonInput<MouseButton>>,
+    actions: Res<Actions>,
     mut events: EventReader<CursorWorldEvent>,
 ) {
-    if m_buttons.just_pressed(MouseButton::Left) {
+    if actions.just_pressed(Action::Place) {
         let ray_pos ;
         if let Some(last) = events.read().last().copied() {
             ray_pos = last.world;
@@ -117,12 +286,25 @@ fn select_building(
 }
 
 fn game_state_control_building(
+    mut commands: Commands,
     mut next_state: ResMut<NextState<GameControlState>>,
-    keys: Res<ButtonInput<KeyCode>>,
+    mut state: ResMut<BuildingControlState>,
+    actions: Res<Actions>,
 ) {
-    if keys.just_pressed(KeyCode::KeyB) {
+    if actions.just_pressed(Action::ToggleBuildMode) {
         next_state.set(GameControlState::Default)
     }
+    // Cancel drops the template, then the tool, then leaves the building state
+    if actions.just_pressed(Action::Cancel) {
+        if let Some(template) = state.cur_building.take() {
+            commands.entity(template).despawn();
+            state.overlaps.clear();
+        } else if state.tool != BuildTool::Place {
+            state.tool = BuildTool::Place;
+        } else {
+            next_state.set(GameControlState::Default)
+        }
+    }
 }
 
 fn state_cleanup_building(
@@ -134,6 +316,7 @@ fn state_cleanup_building(
         commands.entity(building).despawn();
         state.cur_building = None;
     }
+    state.tool = BuildTool::Place;
 
     for e in &ui_query {
         commands.entity(e).despawn();
@@ -192,7 +375,9 @@ fn state_ui_startup_building(
                         mut state: ResMut<BuildingControlState>,
                         grid: Res<WorldGrid>| {
                         let origin = state.cur_cel;
-                        state.cur_size = Vec2::new(4.0, 2.0);
+                        state.tool = BuildTool::Place;
+                        state.cur_kind = BuildingKind::House;
+                        state.cur_size = state.cur_kind.size();
                         let pos = grid.grid_to_world(origin, state.cur_size);
                         spawn_ev.write(RequestSpawnBuildingTemplate { size: state.cur_size, pos: pos });
                     });
@@ -215,10 +400,127 @@ fn state_ui_startup_building(
                         mut state: ResMut<BuildingControlState>,
                         grid: Res<WorldGrid>| {
                         let origin = state.cur_cel;
-                        state.cur_size = Vec2::new(4.0, 5.0);
+                        state.tool = BuildTool::Place;
+                        state.cur_kind = BuildingKind::Workshop;
+                        state.cur_size = state.cur_kind.size();
                         let pos = grid.grid_to_world(origin, state.cur_size);
                         spawn_ev.write(RequestSpawnBuildingTemplate { size: state.cur_size, pos: pos });
                     });
+
+                    ui.spawn((
+                        Name::new("Cellar"),
+                        UiLayout::window()
+                            .anchor(Anchor::Center)
+                            .pos(Rl((40.0, 50.0)))
+                            .size((50.0, 50.0))
+                            .pack(),
+                        Sprite::from_color(
+                            Color::srgba(0.45, 0.3, 0.15, 1.0),
+                            Vec2::new(50.0, 50.0),
+                        ),
+                        OnHoverSetCursor::new(SystemCursorIcon::Pointer),
+                    ))
+                    .observe(|_: Trigger<Pointer<Click>>,
+                        mut spawn_ev: EventWriter<RequestSpawnBuildingTemplate>,
+                        mut state: ResMut<BuildingControlState>,
+                        grid: Res<WorldGrid>| {
+                        let origin = state.cur_cel;
+                        state.tool = BuildTool::Place;
+                        state.cur_kind = BuildingKind::Cellar;
+                        state.cur_size = state.cur_kind.size();
+                        let pos = grid.grid_to_world(origin, state.cur_size);
+                        spawn_ev.write(RequestSpawnBuildingTemplate { size: state.cur_size, pos: pos });
+                    });
+
+                    ui.spawn((
+                        Name::new("Cold Store"),
+                        UiLayout::window()
+                            .anchor(Anchor::Center)
+                            .pos(Rl((50.0, 50.0)))
+                            .size((50.0, 50.0))
+                            .pack(),
+                        Sprite::from_color(
+                            Color::srgba(0.6, 0.85, 1.0, 1.0),
+                            Vec2::new(50.0, 50.0),
+                        ),
+                        OnHoverSetCursor::new(SystemCursorIcon::Pointer),
+                    ))
+                    .observe(|_: Trigger<Pointer<Click>>,
+                        mut spawn_ev: EventWriter<RequestSpawnBuildingTemplate>,
+                        mut state: ResMut<BuildingControlState>,
+                        grid: Res<WorldGrid>| {
+                        let origin = state.cur_cel;
+                        state.tool = BuildTool::Place;
+                        state.cur_kind = BuildingKind::ColdStore;
+                        state.cur_size = state.cur_kind.size();
+                        let pos = grid.grid_to_world(origin, state.cur_size);
+                        spawn_ev.write(RequestSpawnBuildingTemplate { size: state.cur_size, pos: pos });
+                    });
+
+                    ui.spawn((
+                        Name::new("Farm"),
+                        UiLayout::window()
+                            .anchor(Anchor::Center)
+                            .pos(Rl((60.0, 50.0)))
+                            .size((50.0, 50.0))
+                            .pack(),
+                        Sprite::from_color(
+                            Color::srgba(0.4, 0.6, 0.1, 1.0),
+                            Vec2::new(50.0, 50.0),
+                        ),
+                        OnHoverSetCursor::new(SystemCursorIcon::Pointer),
+                    ))
+                    // First click picks the farm tool, further clicks cycle the crop to plant
+                    .observe(|_: Trigger<Pointer<Click>>,
+                        mut commands: Commands,
+                        mut state: ResMut<BuildingControlState>,
+                        mut crop: ResMut<ActiveCrop>,
+                        crops: Res<CropDefinitions>,
+                        mut notifications: EventWriter<Notification>| {
+                        if let Some(template) = state.cur_building.take() {
+                            commands.entity(template).despawn();
+                            state.overlaps.clear();
+                        }
+                        if state.tool == BuildTool::Farm {
+                            crop.0 = (crop.0 + 1) % crops.0.len().max(1);
+                        }
+                        state.tool = BuildTool::Farm;
+                        if let Some(def) = crops.0.get(crop.0) {
+                            notifications.write(Notification::info("Farming", format!("Painting {} fields", def.name)));
+                        }
+                    });
+
+                    ui.spawn((
+                        Name::new("Zone"),
+                        UiLayout::window()
+                            .anchor(Anchor::Center)
+                            .pos(Rl((70.0, 50.0)))
+                            .size((50.0, 50.0))
+                            .pack(),
+                        Sprite::from_color(
+                            Color::srgba(0.3, 0.3, 0.8, 1.0),
+                            Vec2::new(50.0, 50.0),
+                        ),
+                        OnHoverSetCursor::new(SystemCursorIcon::Pointer),
+                    ))
+                    // Each click moves on to the next zone, ending with the eraser
+                    .observe(|_: Trigger<Pointer<Click>>,
+                        mut commands: Commands,
+                        mut state: ResMut<BuildingControlState>,
+                        mut notifications: EventWriter<Notification>| {
+                        if let Some(template) = state.cur_building.take() {
+                            commands.entity(template).despawn();
+                            state.overlaps.clear();
+                        }
+                        let zone = match state.tool {
+                            BuildTool::Zone(Zone::Residential) => Zone::Commercial,
+                            BuildTool::Zone(Zone::Commercial) => Zone::Industrial,
+                            BuildTool::Zone(Zone::Industrial) => Zone::Unzoned,
+                            _ => Zone::Residential,
+                        };
+                        state.tool = BuildTool::Zone(zone);
+                        notifications.write(Notification::info("Zoning", format!("Painting {:?}", zone)));
+                    });
                 });
             });
         });
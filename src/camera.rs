@@ -0,0 +1,232 @@
+pub struct CameraSystems;
+use crate::*;
+use crate::inspector::Selection;
+use crate::notifications::Notification;
+use bevy::input::mouse::{MouseMotion, MouseScrollUnit, MouseWheel};
+
+impl Plugin for CameraSystems {
+    fn build(&self, app: &mut App) {
+        app
+            .insert_resource(CameraConfig::default())
+            .init_resource::<CameraFollow>()
+            .init_resource::<CameraBookmarks>()
+            .add_systems(Update, (toggle_follow, use_bookmarks, follow_target, pan_camera, zoom_camera, clamp_camera).chain());
+    }
+}
+
+// Speeds and limits; the keys and buttons are the pan and zoom actions in `InputBindings`
+#[derive(Resource, Debug, Clone)]
+pub struct CameraConfig {
+    // Screen pixels per second at zoom 1.0, faster when zoomed out
+    pub pan_speed: f32,
+    pub edge_scroll: bool,
+    // Distance from the window edge in pixels that starts edge scrolling
+    pub edge_margin: f32,
+    // Scale change per scroll wheel notch
+    pub zoom_step: f32,
+    // Notches per second while a zoom key is held
+    pub key_zoom_rate: f32,
+    pub min_zoom: f32,
+    pub max_zoom: f32,
+    // How quickly a followed target is caught up with, per second
+    pub follow_smoothing: f32,
+}
+
+impl Default for CameraConfig {
+    fn default() -> Self {
+        Self {
+            pan_speed: 800.0,
+            edge_scroll: true,
+            edge_margin: 12.0,
+            zoom_step: 1.15,
+            key_zoom_rate: 8.0,
+            min_zoom: 0.05,
+            max_zoom: 50.0,
+            follow_smoothing: 5.0,
+        }
+    }
+}
+
+// Entity the camera keeps centred on until the player pans away
+#[derive(Resource, Debug, Default, Clone, Copy)]
+pub struct CameraFollow(pub Option<Entity>);
+
+#[derive(Debug, Clone, Copy)]
+pub struct CameraBookmark {
+    pub position: Vec2,
+    pub zoom: f32,
+}
+
+#[derive(Resource, Debug, Default, Clone)]
+pub struct CameraBookmarks(pub [Option<CameraBookmark>; 9]);
+
+fn ortho_scale(projection: &Projection) -> f32 {
+    match projection {
+        Projection::Orthographic(ortho) => ortho.scale,
+        _ => 1.0,
+    }
+}
+
+// Keyboard, edge-of-screen and mouse drag panning
+fn pan_camera(
+    config: Res<CameraConfig>,
+    actions: Res<Actions>,
+    mut motion: EventReader<MouseMotion>,
+    windows: Query<&Window>,
+    time: Res<Time<Real>>,
+    mut follow: ResMut<CameraFollow>,
+    mut cameras: Query<(&mut Transform, &Projection), With<MainCamera>>,
+) {
+    let drag: Vec2 = motion.read().map(|ev| ev.delta).sum();
+    let Ok((mut transform, projection)) = cameras.single_mut() else { return; };
+    let scale = ortho_scale(projection);
+
+    let mut direction = Vec2::ZERO;
+    if actions.pressed(Action::PanUp) { direction.y += 1.0; }
+    if actions.pressed(Action::PanDown) { direction.y -= 1.0; }
+    if actions.pressed(Action::PanLeft) { direction.x -= 1.0; }
+    if actions.pressed(Action::PanRight) { direction.x += 1.0; }
+
+    if let Ok(window) = windows.single() {
+        if config.edge_scroll && window.focused {
+            if let Some(cursor) = window.cursor_position() {
+                let size = window.size();
+                if cursor.x <= config.edge_margin { direction.x -= 1.0; }
+                if cursor.x >= size.x - config.edge_margin { direction.x += 1.0; }
+                // Screen y grows downwards
+                if cursor.y <= config.edge_margin { direction.y += 1.0; }
+                if cursor.y >= size.y - config.edge_margin { direction.y -= 1.0; }
+            }
+        }
+    }
+
+    // Real time, so panning keeps its speed while the simulation is paused or sped up
+    let mut delta = direction.normalize_or_zero() * config.pan_speed * scale * time.delta_secs();
+    if actions.pressed(Action::PanDrag) {
+        // The world sticks to the cursor while dragging
+        delta += Vec2::new(-drag.x, drag.y) * scale;
+    }
+    if delta == Vec2::ZERO { return; }
+    // Any manual pan takes the camera back from a followed target
+    if follow.0.is_some() {
+        follow.0 = None;
+    }
+    transform.translation += delta.extend(0.0);
+}
+
+// Scrolling zooms around the point under the cursor, the zoom keys around the centre
+fn zoom_camera(
+    config: Res<CameraConfig>,
+    actions: Res<Actions>,
+    time: Res<Time<Real>>,
+    mut scroll: EventReader<MouseWheel>,
+    over_ui: Res<UiBlockHoverCount>,
+    windows: Query<&Window>,
+    mut cameras: Query<(&Camera, &GlobalTransform, &mut Transform, &mut Projection), With<MainCamera>>,
+) {
+    let scrolled: f32 = scroll
+        .read()
+        .map(|ev| match ev.unit {
+            MouseScrollUnit::Line => ev.y,
+            // Touchpads report pixels, roughly 100 to a notch
+            MouseScrollUnit::Pixel => ev.y / 100.0,
+        })
+        .sum();
+    let scrolled = if over_ui.0 > 0 { 0.0 } else { scrolled };
+    let mut keyed = 0.0;
+    if actions.pressed(Action::ZoomIn) { keyed += config.key_zoom_rate * time.delta_secs(); }
+    if actions.pressed(Action::ZoomOut) { keyed -= config.key_zoom_rate * time.delta_secs(); }
+    let notches = scrolled + keyed;
+    if notches == 0.0 { return; }
+    let Ok((camera, camera_transform, mut transform, mut projection)) = cameras.single_mut() else { return; };
+    let Projection::Orthographic(ortho) = &mut *projection else { return; };
+
+    let old_scale = ortho.scale;
+    let new_scale = (old_scale * config.zoom_step.powf(-notches)).clamp(config.min_zoom, config.max_zoom);
+    ortho.scale = new_scale;
+
+    // Keep the world point under the cursor in place
+    let cursor = windows
+        .single()
+        .ok()
+        .filter(|_| scrolled != 0.0)
+        .and_then(|w| w.cursor_position())
+        .and_then(|pos| camera.viewport_to_world_2d(camera_transform, pos).ok());
+    if let Some(anchor) = cursor {
+        let centre = transform.translation.truncate();
+        let moved = anchor - (anchor - centre) * (new_scale / old_scale);
+        transform.translation = moved.extend(transform.translation.z);
+    }
+}
+
+// Follow starts tracking the inspector's selection, or stops if already following
+fn toggle_follow(
+    actions: Res<Actions>,
+    selection: Res<Selection>,
+    mut follow: ResMut<CameraFollow>,
+) {
+    if !actions.just_pressed(Action::Follow) { return; }
+    follow.0 = if follow.0.is_some() { None } else { selection.entity };
+}
+
+// Number keys jump to bookmarks, with the save modifier held they store the current view
+fn use_bookmarks(
+    actions: Res<Actions>,
+    mut bookmarks: ResMut<CameraBookmarks>,
+    mut follow: ResMut<CameraFollow>,
+    mut cameras: Query<(&mut Transform, &mut Projection), With<MainCamera>>,
+    mut notifications: EventWriter<Notification>,
+) {
+    let Some(slot) = Action::BOOKMARKS.iter().position(|a| actions.just_pressed(*a)) else { return; };
+    let Ok((mut transform, mut projection)) = cameras.single_mut() else { return; };
+
+    if actions.pressed(Action::SaveBookmark) {
+        bookmarks.0[slot] = Some(CameraBookmark { position: transform.translation.truncate(), zoom: ortho_scale(&projection) });
+        notifications.write(Notification::info("Camera", format!("Saved bookmark {}", slot + 1)));
+        return;
+    }
+    let Some(bookmark) = bookmarks.0[slot] else { return; };
+    follow.0 = None;
+    transform.translation = bookmark.position.extend(transform.translation.z);
+    if let Projection::Orthographic(ortho) = &mut *projection {
+        ortho.scale = bookmark.zoom;
+    }
+}
+
+// Eases the camera towards the followed entity, dropping it once it is gone
+fn follow_target(
+    config: Res<CameraConfig>,
+    time: Res<Time<Real>>,
+    mut follow: ResMut<CameraFollow>,
+    targets: Query<&GlobalTransform>,
+    mut cameras: Query<&mut Transform, With<MainCamera>>,
+) {
+    let Some(target) = follow.0 else { return; };
+    let Ok(target_transform) = targets.get(target) else {
+        follow.0 = None;
+        return;
+    };
+    let Ok(mut transform) = cameras.single_mut() else { return; };
+    let goal = target_transform.translation().truncate();
+    // Frame-rate independent exponential smoothing
+    let t = 1.0 - (-config.follow_smoothing * time.delta_secs()).exp();
+    let next = transform.translation.truncate().lerp(goal, t);
+    transform.translation = next.extend(transform.translation.z);
+}
+
+// Keeps the view over the grid; a view wider than the grid stays centred on it
+fn clamp_camera(
+    grid: Res<WorldGrid>,
+    windows: Query<&Window>,
+    mut cameras: Query<(&mut Transform, &Projection), With<MainCamera>>,
+) {
+    let Ok(window) = windows.single() else { return; };
+    let Ok((mut transform, projection)) = cameras.single_mut() else { return; };
+    let half_world = Vec2::new(grid.width() as f32, grid.height() as f32) * grid.scale() as f32 / 2.0;
+    let half_view = window.size() * ortho_scale(projection) / 2.0;
+    let limit = (half_world - half_view).max(Vec2::ZERO);
+    let clamped = transform.translation.truncate().clamp(-limit, limit);
+    if clamped != transform.translation.truncate() {
+        transform.translation = clamped.extend(transform.translation.z);
+    }
+}
@@ -0,0 +1,315 @@
+pub struct ChartsSystems;
+use crate::*;
+use crate::metrics::{Metrics, Series};
+use crate::production::Good;
+use bevy::prelude::*;
+use bevy::sprite::Anchor;
+
+impl Plugin for ChartsSystems {
+    fn build(&self, app: &mut App) {
+        app
+            .insert_resource(ChartsConfig::default())
+            .init_resource::<ChartsState>()
+            .add_systems(Update, (toggle_charts, update_chart_label, draw_chart).chain());
+    }
+}
+
+#[derive(Resource, Debug, Clone)]
+pub struct ChartsConfig {
+    // Most recent samples shown across the plot width
+    pub visible_samples: usize,
+}
+
+impl Default for ChartsConfig {
+    fn default() -> Self {
+        Self {
+            visible_samples: 240,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ChartMetric {
+    #[default]
+    Money,
+    Stock,
+    Production,
+    Spoilage,
+    Prices,
+    Needs,
+}
+
+impl ChartMetric {
+    const ALL: [ChartMetric; 6] = [ChartMetric::Money, ChartMetric::Stock, ChartMetric::Production, ChartMetric::Spoilage, ChartMetric::Prices, ChartMetric::Needs];
+
+    fn name(&self) -> &'static str {
+        match self {
+            ChartMetric::Money => "Money",
+            ChartMetric::Stock => "Stock",
+            ChartMetric::Production => "Production",
+            ChartMetric::Spoilage => "Spoilage",
+            ChartMetric::Prices => "Prices",
+            ChartMetric::Needs => "Needs",
+        }
+    }
+
+    fn next(&self) -> ChartMetric {
+        let i = Self::ALL.iter().position(|m| m == self).unwrap_or(0);
+        Self::ALL[(i + 1) % Self::ALL.len()]
+    }
+}
+
+// What the charts window shows; `business` is None for the whole economy
+#[derive(Resource, Debug, Clone, Default)]
+pub struct ChartsState {
+    pub open: bool,
+    pub metric: ChartMetric,
+    pub business: Option<String>,
+    hovered: bool,
+}
+
+#[derive(Component)]
+struct ChartsUi;
+
+#[derive(Component)]
+struct ChartPlot;
+
+#[derive(Component)]
+struct ChartLabel;
+
+const LINE_COLORS: [Color; 6] = [
+    Color::srgb(0.95, 0.75, 0.2),
+    Color::srgb(0.3, 0.8, 0.95),
+    Color::srgb(0.55, 0.9, 0.4),
+    Color::srgb(0.95, 0.4, 0.4),
+    Color::srgb(0.8, 0.55, 0.95),
+    Color::srgb(0.9, 0.9, 0.9),
+];
+
+// Business names that have recorded data, taken from the "business/<name>/money" series
+fn recorded_businesses(metrics: &Metrics) -> Vec<String> {
+    metrics
+        .names()
+        .filter_map(|n| n.strip_prefix("business/")?.strip_suffix("/money"))
+        .map(|n| n.to_string())
+        .collect()
+}
+
+// (legend, series key) pairs plotted for the current selection
+fn chart_lines(state: &ChartsState, metrics: &Metrics) -> Vec<(String, String)> {
+    let per_good = |prefix: &str| -> Vec<(String, String)> {
+        Good::ALL.iter().map(|g| (g.name().to_string(), format!("{}/{}", prefix, g.name()))).collect()
+    };
+    match (state.metric, &state.business) {
+        (ChartMetric::Money, None) => recorded_businesses(metrics)
+            .into_iter()
+            .map(|b| (b.clone(), Metrics::business_key(&b, "money")))
+            .collect(),
+        (ChartMetric::Money, Some(b)) => vec![(b.clone(), Metrics::business_key(b, "money"))],
+        (ChartMetric::Stock, None) => per_good("stock"),
+        (ChartMetric::Stock, Some(b)) => per_good(&Metrics::business_key(b, "stock")),
+        (ChartMetric::Production, None) => per_good("production"),
+        (ChartMetric::Production, Some(b)) => per_good(&Metrics::business_key(b, "production")),
+        (ChartMetric::Spoilage, None) => per_good("spoiled"),
+        (ChartMetric::Spoilage, Some(b)) => per_good(&Metrics::business_key(b, "spoiled")),
+        // Prices and needs are economy-wide regardless of the selected business
+        (ChartMetric::Prices, _) => per_good("price"),
+        (ChartMetric::Needs, _) => ["health", "hunger", "thirst", "sleep"]
+            .iter()
+            .map(|n| (n.to_string(), format!("needs/{}", n)))
+            .collect(),
+    }
+}
+
+fn visible<'a>(series: &'a Series, samples: usize) -> impl Iterator<Item = &'a (u64, f32)> {
+    series.points.iter().skip(series.points.len().saturating_sub(samples))
+}
+
+fn toggle_charts(
+    mut commands: Commands,
+    actions: Res<Actions>,
+    mut state: ResMut<ChartsState>,
+    mut hover: ResMut<UiBlockHoverCount>,
+    camera_q: Query<Entity, With<MainCamera>>,
+    ui_query: Query<Entity, With<ChartsUi>>,
+) {
+    if !actions.just_pressed(Action::ToggleCharts) { return; }
+    state.open = !state.open;
+
+    if !state.open {
+        for e in &ui_query {
+            commands.entity(e).despawn();
+        }
+        // The window may vanish under the cursor without a Pointer<Out>
+        if state.hovered && hover.0 > 0 { hover.0 -= 1; }
+        state.hovered = false;
+        return;
+    }
+
+    let Ok(camera) = camera_q.single() else { return; };
+    commands.entity(camera).with_children(|cam| {
+        cam.spawn((
+            Name::new("Charts Root"),
+            UiLayoutRoot::new_2d(),
+            UiFetchFromCamera::<0>,
+            ChartsUi,
+        ))
+        .with_children(|ui| {
+            ui.spawn((
+                Name::new("Charts Background"),
+                UiLayout::window()
+                    .anchor(Anchor::Center)
+                    .pos(Rl((50.0, 45.0)))
+                    .size(Rl((60.0, 55.0)))
+                    .pack(),
+                Sprite::from_color(Color::srgba(0.08, 0.08, 0.1, 0.9), Vec2::new(50.0, 50.0)),
+            ))
+            .observe(|_: Trigger<Pointer<Over>>, mut cnt: ResMut<UiBlockHoverCount>, mut state: ResMut<ChartsState>| {
+                cnt.0 += 1;
+                state.hovered = true;
+            })
+            .observe(|_: Trigger<Pointer<Out>>, mut cnt: ResMut<UiBlockHoverCount>, mut state: ResMut<ChartsState>| {
+                if cnt.0 > 0 { cnt.0 -= 1; }
+                state.hovered = false;
+            })
+            .with_children(|ui| {
+                ui.spawn((
+                    Name::new("Charts Label"),
+                    UiLayout::window()
+                        .anchor(Anchor::TopLeft)
+                        .pos(Rl((3.0, 3.0)))
+                        .pack(),
+                    UiTextSize::from(Rh(5.0)),
+                    Text2d::new(""),
+                    Anchor::TopLeft,
+                    ChartLabel,
+                ));
+
+                ui.spawn((
+                    Name::new("Charts Plot"),
+                    UiLayout::window()
+                        .pos(Rl((3.0, 22.0)))
+                        .size(Rl((94.0, 62.0)))
+                        .pack(),
+                    Sprite::from_color(Color::srgba(0.0, 0.0, 0.0, 0.5), Vec2::new(50.0, 50.0)),
+                    ChartPlot,
+                ));
+
+                ui.spawn((
+                    Name::new("Charts Metric Button"),
+                    UiLayout::window()
+                        .pos(Rl((3.0, 88.0)))
+                        .size(Rl((30.0, 9.0)))
+                        .pack(),
+                    Sprite::from_color(Color::srgba(0.3, 0.3, 0.35, 1.0), Vec2::new(50.0, 50.0)),
+                    OnHoverSetCursor::new(SystemCursorIcon::Pointer),
+                ))
+                .observe(|_: Trigger<Pointer<Click>>, mut state: ResMut<ChartsState>| {
+                    state.metric = state.metric.next();
+                })
+                .with_children(|ui| {
+                    ui.spawn((
+                        UiLayout::window().anchor(Anchor::Center).pos(Rl((50.0, 50.0))).pack(),
+                        UiTextSize::from(Rh(60.0)),
+                        Text2d::new("Next metric"),
+                        Pickable::IGNORE,
+                    ));
+                });
+
+                ui.spawn((
+                    Name::new("Charts Scope Button"),
+                    UiLayout::window()
+                        .pos(Rl((36.0, 88.0)))
+                        .size(Rl((30.0, 9.0)))
+                        .pack(),
+                    Sprite::from_color(Color::srgba(0.3, 0.3, 0.35, 1.0), Vec2::new(50.0, 50.0)),
+                    OnHoverSetCursor::new(SystemCursorIcon::Pointer),
+                ))
+                // Cycles global -> each recorded business -> global
+                .observe(|_: Trigger<Pointer<Click>>, mut state: ResMut<ChartsState>, metrics: Res<Metrics>| {
+                    let businesses = recorded_businesses(&metrics);
+                    let next = match &state.business {
+                        None => 0,
+                        Some(current) => businesses.iter().position(|b| b == current).map_or(0, |i| i + 1),
+                    };
+                    state.business = businesses.get(next).cloned();
+                })
+                .with_children(|ui| {
+                    ui.spawn((
+                        UiLayout::window().anchor(Anchor::Center).pos(Rl((50.0, 50.0))).pack(),
+                        UiTextSize::from(Rh(60.0)),
+                        Text2d::new("Next business"),
+                        Pickable::IGNORE,
+                    ));
+                });
+            });
+        });
+    });
+}
+
+fn update_chart_label(
+    state: Res<ChartsState>,
+    config: Res<ChartsConfig>,
+    metrics: Res<Metrics>,
+    mut labels: Query<&mut Text2d, With<ChartLabel>>,
+) {
+    if !state.open || !(state.is_changed() || metrics.is_changed()) { return; }
+    let Ok(mut text) = labels.single_mut() else { return; };
+
+    let scope = state.business.as_deref().unwrap_or("All businesses");
+    let mut lines = vec![format!("{} - {}   (tick {})", state.metric.name(), scope, metrics.tick())];
+    let legend: Vec<String> = chart_lines(&state, &metrics)
+        .iter()
+        .filter_map(|(legend, key)| {
+            let series = metrics.series(key)?;
+            let (min, max) = visible(series, config.visible_samples)
+                .fold((f32::MAX, f32::MIN), |(lo, hi), (_, v)| (lo.min(*v), hi.max(*v)));
+            Some(format!("{} {:.1} [{:.1}..{:.1}]", legend, series.last()?, min, max))
+        })
+        .collect();
+    lines.push(if legend.is_empty() { "No data recorded yet".to_string() } else { legend.join("   ") });
+    text.0 = lines.join("\n");
+}
+
+// Line colours follow the legend order in the label
+fn draw_chart(
+    state: Res<ChartsState>,
+    config: Res<ChartsConfig>,
+    metrics: Res<Metrics>,
+    plots: Query<(&GlobalTransform, &Sprite), With<ChartPlot>>,
+    mut gizmos: Gizmos,
+) {
+    if !state.open { return; }
+    let Ok((transform, sprite)) = plots.single() else { return; };
+    let Some(size) = sprite.custom_size else { return; };
+    let center = transform.translation().truncate();
+    let bottom_left = center - size / 2.0;
+
+    let lines: Vec<&Series> = chart_lines(&state, &metrics)
+        .iter()
+        .filter_map(|(_, key)| metrics.series(key))
+        .collect();
+    // Shared y range so lines are comparable, always including zero
+    let (min, max) = lines
+        .iter()
+        .flat_map(|s| visible(s, config.visible_samples))
+        .fold((0.0f32, 0.0f32), |(lo, hi), (_, v)| (lo.min(*v), hi.max(*v)));
+    let range = (max - min).max(1.0);
+    let last_tick = metrics.tick();
+    let step = size.x / config.visible_samples.max(2) as f32;
+
+    // Zero line
+    let zero_y = bottom_left.y + (0.0 - min) / range * size.y;
+    gizmos.line_2d(Vec2::new(bottom_left.x, zero_y), Vec2::new(bottom_left.x + size.x, zero_y), Color::srgba(1.0, 1.0, 1.0, 0.3));
+
+    for (i, series) in lines.iter().enumerate() {
+        let points = visible(series, config.visible_samples).map(|(tick, v)| {
+            let age = last_tick.saturating_sub(*tick) as f32;
+            Vec2::new(
+                bottom_left.x + size.x - age * step,
+                bottom_left.y + (v - min) / range * size.y,
+            )
+        });
+        gizmos.linestrip_2d(points, LINE_COLORS[i % LINE_COLORS.len()]);
+    }
+}
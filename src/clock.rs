@@ -0,0 +1,206 @@
+pub struct ClockSystems;
+use crate::*;
+use crate::notifications::Notification;
+use crate::population::Home;
+use bevy::prelude::*;
+use bevy::ecs::schedule::common_conditions::on_event;
+
+impl Plugin for ClockSystems {
+    fn build(&self, app: &mut App) {
+        let config = ClockConfig::default();
+        app
+            .insert_resource(GameClock { total_minutes: config.start_hour as u64 * 60 })
+            .insert_resource(config)
+            .add_systems(Startup, spawn_daylight_overlay)
+            .add_systems(Update, (advance_clock, apply_schedules).chain().run_if(on_event::<WorldTick>))
+            .add_systems(Update, (update_daylight_overlay, toggle_pause));
+    }
+}
+
+#[derive(Resource, Debug, Clone, Copy)]
+pub struct ClockConfig {
+    pub minutes_per_tick: u32,
+    pub start_hour: u32,
+    pub days_per_season: u32,
+}
+
+impl Default for ClockConfig {
+    fn default() -> Self {
+        Self {
+            minutes_per_tick: 10,
+            start_hour: 6,
+            days_per_season: 7,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, serde::Deserialize)]
+pub enum Season {
+    Spring,
+    Summer,
+    Autumn,
+    Winter,
+}
+
+// In-game calendar, advanced by `ClockConfig::minutes_per_tick` on every `WorldTick`
+#[derive(Resource, Debug, Clone, Copy, Default)]
+pub struct GameClock {
+    pub total_minutes: u64,
+}
+
+impl GameClock {
+    pub fn minute(&self) -> u32 {
+        (self.total_minutes % 60) as u32
+    }
+
+    pub fn hour(&self) -> u32 {
+        ((self.total_minutes / 60) % 24) as u32
+    }
+
+    // Hour of day with minutes as the fraction, 0.0..24.0
+    pub fn hour_f32(&self) -> f32 {
+        (self.total_minutes % (24 * 60)) as f32 / 60.0
+    }
+
+    pub fn day(&self) -> u32 {
+        (self.total_minutes / (24 * 60)) as u32
+    }
+
+    pub fn season(&self, config: &ClockConfig) -> Season {
+        match (self.day() / config.days_per_season.max(1)) % 4 {
+            0 => Season::Spring,
+            1 => Season::Summer,
+            2 => Season::Autumn,
+            _ => Season::Winter,
+        }
+    }
+}
+
+// True if `hour` lies in `start..end`, wrapping past midnight when `start > end`
+pub fn hour_in_range(hour: u32, start: u32, end: u32) -> bool {
+    if start <= end {
+        hour >= start && hour < end
+    } else {
+        hour >= start || hour < end
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct ScheduleBlock {
+    pub start_hour: u32,
+    pub end_hour: u32,
+    pub activity: Activity,
+}
+
+// What a character should be doing at each hour. Hours not covered by a block are free time.
+#[derive(Component, Debug, Clone)]
+pub struct DailySchedule {
+    pub blocks: Vec<ScheduleBlock>,
+}
+
+impl DailySchedule {
+    // Night sleep followed by a work shift starting at `shift_start`
+    pub fn worker(shift_start: u32, shift_length: u32) -> Self {
+        Self {
+            blocks: vec![
+                ScheduleBlock { start_hour: 22, end_hour: 6, activity: Activity::Sleeping },
+                ScheduleBlock { start_hour: shift_start, end_hour: (shift_start + shift_length) % 24, activity: Activity::Working },
+            ],
+        }
+    }
+
+    pub fn activity_at(&self, hour: u32) -> Activity {
+        self.blocks
+            .iter()
+            .find(|b| hour_in_range(hour, b.start_hour, b.end_hour))
+            .map(|b| b.activity)
+            .unwrap_or(Activity::Idle)
+    }
+}
+
+#[derive(Component, Debug, Clone, Copy)]
+pub struct OpeningHours {
+    pub open: u32,
+    pub close: u32,
+}
+
+impl OpeningHours {
+    pub fn is_open(&self, hour: u32) -> bool {
+        hour_in_range(hour, self.open, self.close)
+    }
+}
+
+#[derive(Component)]
+struct DaylightOverlay;
+
+fn advance_clock(mut clock: ResMut<GameClock>, config: Res<ClockConfig>) {
+    clock.total_minutes += config.minutes_per_tick as u64;
+}
+
+fn apply_schedules(
+    clock: Res<GameClock>,
+    mut query: Query<(&DailySchedule, &mut Activity, Option<&Home>, &mut Destination), Without<Asleep>>,
+    homes: Query<&Transform>,
+) {
+    let hour = clock.hour();
+    for (schedule, mut activity, home, mut destination) in &mut query {
+        let scheduled = schedule.activity_at(hour);
+        if *activity == scheduled { continue; }
+        *activity = scheduled;
+        // Head home to sleep; the house bed takes over on arrival
+        if scheduled == Activity::Sleeping {
+            if let Some(transform) = home.and_then(|h| homes.get(h.0).ok()) {
+                destination.0 = transform.translation.truncate();
+            }
+        }
+    }
+}
+
+fn spawn_daylight_overlay(mut commands: Commands) {
+    commands.spawn((
+        Node {
+            position_type: PositionType::Absolute,
+            width: Val::Percent(100.0),
+            height: Val::Percent(100.0),
+            ..Default::default()
+        },
+        BackgroundColor(Color::NONE),
+        // Keep other UI on top and let clicks through
+        GlobalZIndex(-1),
+        Pickable::IGNORE,
+        DaylightOverlay,
+    ));
+}
+
+// Darkness 0..1 over the day: full light 8..18, dark 21..5, linear ramps between
+fn darkness_at(hour: f32) -> f32 {
+    match hour {
+        h if h < 5.0 => 1.0,
+        h if h < 8.0 => 1.0 - (h - 5.0) / 3.0,
+        h if h < 18.0 => 0.0,
+        h if h < 21.0 => (h - 18.0) / 3.0,
+        _ => 1.0,
+    }
+}
+
+// Stopping virtual time halts world ticks and movement; the camera keeps real time
+fn toggle_pause(actions: Res<Actions>, mut time: ResMut<Time<Virtual>>, mut notifications: EventWriter<Notification>) {
+    if !actions.just_pressed(Action::Pause) { return; }
+    if time.is_paused() {
+        time.unpause();
+        notifications.write(Notification::info("Clock", "Resumed"));
+    } else {
+        time.pause();
+        notifications.write(Notification::info("Clock", "Paused"));
+    }
+}
+
+fn update_daylight_overlay(
+    clock: Res<GameClock>,
+    mut overlay: Query<&mut BackgroundColor, With<DaylightOverlay>>,
+) {
+    if !clock.is_changed() { return; }
+    let Ok(mut color) = overlay.single_mut() else { return; };
+    let max_alpha = 0.55;
+    color.0 = Color::srgba(0.02, 0.02, 0.12, darkness_at(clock.hour_f32()) * max_alpha);
+}
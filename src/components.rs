@@ -1,5 +1,7 @@
+use std::marker::PhantomData;
 use bevy::prelude::*;
 use bevy_rapier2d::prelude::*;
+use serde::Deserialize;
 
 #[derive(Component)]
 pub struct Health(pub f32);
@@ -7,22 +9,50 @@ pub struct Health(pub f32);
 #[derive(Component)]
 pub struct MainCamera;
 
-#[derive(Component)]
-pub struct Hunger{
-    pub value: f32,
-    pub decay: fn(&f32) -> f32,
+// Marker for one kind of need. `KEY` names its entry in the needs config file.
+pub trait NeedKind: Send + Sync + 'static {
+    const KEY: &'static str;
 }
 
 #[derive(Component)]
-pub struct Thirst{
+pub struct Need<K: NeedKind> {
     pub value: f32,
-    pub decay: fn(&f32) -> f32,
+    // Per-individual multiplier on the configured decay rate
+    pub rate_scale: f32,
+    _kind: PhantomData<K>,
 }
 
-#[derive(Component)]
-pub struct Sleep{
-    pub value: f32,
-    pub decay: fn(&f32) -> f32,
+impl<K: NeedKind> Need<K> {
+    pub fn new(value: f32) -> Self {
+        Self { value, rate_scale: 1.0, _kind: PhantomData }
+    }
+
+    pub fn with_rate_scale(mut self, rate_scale: f32) -> Self {
+        self.rate_scale = rate_scale;
+        self
+    }
+}
+
+pub struct HungerNeed;
+impl NeedKind for HungerNeed { const KEY: &'static str = "hunger"; }
+
+pub struct ThirstNeed;
+impl NeedKind for ThirstNeed { const KEY: &'static str = "thirst"; }
+
+pub struct SleepNeed;
+impl NeedKind for SleepNeed { const KEY: &'static str = "sleep"; }
+
+pub type Hunger = Need<HungerNeed>;
+pub type Thirst = Need<ThirstNeed>;
+pub type Sleep = Need<SleepNeed>;
+
+// What a character is currently doing. Scales need decay through the configured multipliers.
+#[derive(Component, Default, Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize)]
+pub enum Activity {
+    #[default]
+    Idle,
+    Working,
+    Sleeping,
 }
 
 #[derive(Component)]
@@ -40,6 +70,17 @@ pub struct Destination(pub Vec2);
 #[derive(Component)]
 pub struct Food(pub f32);
 
+#[derive(Component)]
+pub struct WaterSource(pub f32);
+
+#[derive(Component)]
+pub struct Bed;
+
+#[derive(Component)]
+pub struct Asleep {
+    pub bed: Entity,
+}
+
 #[derive(Component)]
 pub struct Building;
 
@@ -56,6 +97,7 @@ pub struct CharacterBundle {
     pub hunger: Hunger,
     pub thirst: Thirst,
     pub sleep: Sleep,
+    pub activity: Activity,
     pub speed: Speed,
     pub velocity: Velocity,
     pub destination: Destination,
@@ -0,0 +1,336 @@
+pub struct FarmingSystems;
+use crate::*;
+use crate::building::{BuildTool, BuildingPlaced};
+use crate::clock::{ClockConfig, GameClock, Season};
+use crate::jobs::{Job, JobBoard, JobCategory, JobCompleted, Task};
+use crate::notifications::Notification;
+use crate::ownership::{ActiveBusiness, Owner};
+use crate::production::{Batch, Good, GoodsProduced, Storage};
+use crate::skills::Skill;
+use crate::storage::{spill_to_stockpile, GroundStockpile, StorageCapacity, StorageConfig};
+use crate::world_grid::TERRAIN_FARM;
+use bevy::prelude::*;
+use bevy::ecs::schedule::common_conditions::on_event;
+use std::collections::HashMap;
+use serde::Deserialize;
+
+const CROPS_CONFIG_PATH: &str = "assets/config/crops.ron";
+
+impl Plugin for FarmingSystems {
+    fn build(&self, app: &mut App) {
+        app
+            .insert_resource(CropDefinitions::load(CROPS_CONFIG_PATH))
+            .insert_resource(FarmingConfig::default())
+            .init_resource::<ActiveCrop>()
+            .add_systems(Update, (grow_crops, post_farm_jobs).chain().run_if(on_event::<WorldTick>))
+            .add_systems(Update, (apply_farm_work, remove_built_over_plots, update_plot_visuals))
+            .add_systems(Update, (paint_farm_plots, clear_farm_plot).run_if(in_state(GameControlState::Building)));
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct CropDefinition {
+    pub name: String,
+    pub good: Good,
+    // Game days spent in each growth stage; the crop is ripe after the last one
+    pub stage_days: Vec<f32>,
+    pub yield_per_plot: i32,
+    // Growth speed per season, missing seasons do not grow the crop at all
+    pub season_growth: HashMap<Season, f32>,
+    // A growing plot pauses until it has been tended this often
+    pub tend_every_days: f32,
+}
+
+impl CropDefinition {
+    pub fn days_to_ripen(&self) -> f32 {
+        self.stage_days.iter().sum()
+    }
+
+    pub fn season_rate(&self, season: Season) -> f32 {
+        self.season_growth.get(&season).copied().unwrap_or(0.0)
+    }
+
+    // Index of the stage reached after `growth_days`
+    pub fn stage(&self, growth_days: f32) -> usize {
+        let mut elapsed = 0.0;
+        for (i, days) in self.stage_days.iter().enumerate() {
+            elapsed += days;
+            if growth_days < elapsed { return i; }
+        }
+        self.stage_days.len()
+    }
+}
+
+// Crops that can be planted, read from `assets/config/crops.ron`
+#[derive(Resource, Debug, Clone, Deserialize)]
+#[serde(transparent)]
+pub struct CropDefinitions(pub Vec<CropDefinition>);
+
+impl Default for CropDefinitions {
+    fn default() -> Self {
+        Self(vec![CropDefinition {
+            name: "Wheat".to_string(),
+            good: Good::Grain,
+            stage_days: vec![1.0, 2.0, 2.0],
+            yield_per_plot: 4,
+            season_growth: HashMap::from([(Season::Spring, 1.0), (Season::Summer, 1.2), (Season::Autumn, 0.6)]),
+            tend_every_days: 2.0,
+        }])
+    }
+}
+
+impl CropDefinitions {
+    pub fn load(path: &str) -> Self {
+        let Ok(text) = std::fs::read_to_string(path) else {
+            warn!("[farming] No config at {path}, using default crops");
+            return Self::default();
+        };
+        match ron::from_str::<Self>(&text) {
+            Ok(defs) if !defs.0.is_empty() => defs,
+            Ok(_) => {
+                warn!("[farming] {path} defines no crops, using default crops");
+                Self::default()
+            }
+            Err(e) => {
+                warn!("[farming] Failed to parse {path}: {e}, using default crops");
+                Self::default()
+            }
+        }
+    }
+}
+
+#[derive(Resource, Debug, Clone, Copy)]
+pub struct FarmingConfig {
+    // Ticks of work for each farm job
+    pub plant_ticks: u32,
+    pub tend_ticks: u32,
+    pub harvest_ticks: u32,
+    pub job_priority: i32,
+}
+
+impl Default for FarmingConfig {
+    fn default() -> Self {
+        Self {
+            plant_ticks: 3,
+            tend_ticks: 2,
+            harvest_ticks: 4,
+            job_priority: 2,
+        }
+    }
+}
+
+// Index into `CropDefinitions` of the crop newly painted plots are sown with
+#[derive(Resource, Debug, Default, Clone, Copy)]
+pub struct ActiveCrop(pub usize);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PlotState {
+    // Waiting to be planted
+    Fallow,
+    Growing,
+    // Waiting to be harvested
+    Ripe,
+}
+
+// One farmed grid cell, owned by the business that gets its harvest
+#[derive(Component, Debug, Clone)]
+pub struct FarmPlot {
+    pub cell: Vec2,
+    pub crop: usize,
+    pub state: PlotState,
+    pub growth_days: f32,
+    pub days_since_tended: f32,
+}
+
+impl FarmPlot {
+    fn needs_tending(&self, crop: &CropDefinition) -> bool {
+        self.state == PlotState::Growing && self.days_since_tended >= crop.tend_every_days
+    }
+}
+
+fn days_per_tick(clock_config: &ClockConfig) -> f32 {
+    clock_config.minutes_per_tick as f32 / (24.0 * 60.0)
+}
+
+// Dragging with the farm tool turns free tiles into plots of the active crop
+fn paint_farm_plots(
+    mut commands: Commands,
+    actions: Res<Actions>,
+    state: Res<BuildingControlState>,
+    over_ui: Res<UiBlockHoverCount>,
+    active: Res<ActiveBusiness>,
+    crop: Res<ActiveCrop>,
+    crops: Res<CropDefinitions>,
+    mut grid: ResMut<WorldGrid>,
+    mut notifications: EventWriter<Notification>,
+) {
+    if state.tool != BuildTool::Farm || over_ui.0 > 0 || !actions.pressed(Action::Place) { return; }
+    // Harvests go into a business' storage, so a farm needs one to belong to
+    let Some(owner) = active.0 else {
+        if actions.just_pressed(Action::Place) {
+            notifications.write(Notification::warning("Farming", "No active business to own the field"));
+        }
+        return;
+    };
+    let cell = state.cur_cel;
+    if !grid.tile(cell).is_some_and(|t| t.terrain_type == 0) { return; }
+
+    grid.set_terrain(cell, TERRAIN_FARM);
+    let pos = grid.grid_to_world(cell, Vec2::ONE);
+    let crop_index = crop.0.min(crops.0.len().saturating_sub(1));
+    commands.spawn((
+        EntityLabel(format!("{} field", crops.0[crop_index].name)),
+        FarmPlot { cell, crop: crop_index, state: PlotState::Fallow, growth_days: 0.0, days_since_tended: 0.0 },
+        Owner(owner),
+        Sprite::from_color(Color::srgb(0.45, 0.3, 0.15), Vec2::splat(grid.scale() as f32)),
+        Transform::from_xyz(pos.x, pos.y, 0.05),
+    ));
+}
+
+// Demolishing with the farm tool removes the plot under the cursor
+fn clear_farm_plot(
+    mut commands: Commands,
+    actions: Res<Actions>,
+    state: Res<BuildingControlState>,
+    mut grid: ResMut<WorldGrid>,
+    plots: Query<(Entity, &FarmPlot)>,
+) {
+    if state.tool != BuildTool::Farm || !actions.just_pressed(Action::Demolish) { return; }
+    if let Some((entity, plot)) = plots.iter().find(|(_, p)| p.cell == state.cur_cel) {
+        grid.set_terrain(plot.cell, 0);
+        commands.entity(entity).despawn();
+    }
+}
+
+// Buildings placed over fields claim their tiles, the plots underneath are lost
+fn remove_built_over_plots(
+    mut commands: Commands,
+    mut placed: EventReader<BuildingPlaced>,
+    grid: Res<WorldGrid>,
+    plots: Query<(Entity, &FarmPlot)>,
+) {
+    if placed.read().count() == 0 { return; }
+    for (entity, plot) in &plots {
+        if !grid.tile(plot.cell).is_some_and(|t| t.terrain_type == TERRAIN_FARM) {
+            commands.entity(entity).despawn();
+        }
+    }
+}
+
+// Advances planted crops by the tick's share of a day, scaled by soil fertility and season
+fn grow_crops(
+    clock: Res<GameClock>,
+    clock_config: Res<ClockConfig>,
+    crops: Res<CropDefinitions>,
+    grid: Res<WorldGrid>,
+    mut plots: Query<&mut FarmPlot>,
+) {
+    let days = days_per_tick(&clock_config);
+    let season = clock.season(&clock_config);
+    for mut plot in &mut plots {
+        if plot.state != PlotState::Growing { continue; }
+        let Some(crop) = crops.0.get(plot.crop) else { continue; };
+        plot.days_since_tended += days;
+        // Neglected crops stand still until a farmer comes by
+        if plot.needs_tending(crop) { continue; }
+        let fertility = grid.tile(plot.cell).map_or(1.0, |t| t.fertility);
+        plot.growth_days += days * fertility * crop.season_rate(season);
+        if plot.growth_days >= crop.days_to_ripen() {
+            plot.state = PlotState::Ripe;
+        }
+    }
+}
+
+// Posts one planting, tending or harvest job per plot that needs a farmer
+fn post_farm_jobs(
+    config: Res<FarmingConfig>,
+    clock: Res<GameClock>,
+    clock_config: Res<ClockConfig>,
+    crops: Res<CropDefinitions>,
+    mut board: ResMut<JobBoard>,
+    plots: Query<(Entity, &FarmPlot)>,
+) {
+    let season = clock.season(&clock_config);
+    for (entity, plot) in &plots {
+        let Some(crop) = crops.0.get(plot.crop) else { continue; };
+        let ticks = match plot.state {
+            // No sowing out of season
+            PlotState::Fallow if crop.season_rate(season) > 0.0 => config.plant_ticks,
+            PlotState::Growing if plot.needs_tending(crop) => config.tend_ticks,
+            PlotState::Ripe => config.harvest_ticks,
+            _ => continue,
+        };
+        if board.count_posted_by(entity, JobCategory::Harvest) > 0 { continue; }
+        // Anyone may farm; the farming skill decides who is drawn to it
+        board.post(Job::new(JobCategory::Harvest, config.job_priority, entity, vec![
+            Task::MoveTo(entity),
+            Task::Work { target: entity, ticks },
+        ]).with_skill(Skill::Farming, 0.0));
+    }
+}
+
+// Finished farm jobs move their plot on; harvests go to the owning business
+fn apply_farm_work(
+    mut commands: Commands,
+    mut completed: EventReader<JobCompleted>,
+    crops: Res<CropDefinitions>,
+    storage_config: Res<StorageConfig>,
+    mut plots: Query<(&mut FarmPlot, &Owner, &GlobalTransform)>,
+    mut storages: Query<&mut Storage>,
+    capacities: Query<&StorageCapacity>,
+    stockpiles: Query<(Entity, &Owner, &GlobalTransform), With<GroundStockpile>>,
+    mut produced: EventWriter<GoodsProduced>,
+) {
+    for ev in completed.read() {
+        if ev.category != JobCategory::Harvest { continue; }
+        let Ok((mut plot, owner, transform)) = plots.get_mut(ev.poster) else { continue; };
+        let Some(crop) = crops.0.get(plot.crop) else { continue; };
+        match plot.state {
+            PlotState::Fallow => {
+                plot.state = PlotState::Growing;
+                plot.growth_days = 0.0;
+                plot.days_since_tended = 0.0;
+            }
+            PlotState::Growing => plot.days_since_tended = 0.0,
+            PlotState::Ripe => {
+                let business = owner.0;
+                let room = match (capacities.get(business), storages.get(business)) {
+                    (Ok(capacity), Ok(storage)) => capacity.room_for(&storage, crop.good),
+                    _ => i32::MAX,
+                };
+                let stored = crop.yield_per_plot.min(room);
+                if let Ok(mut storage) = storages.get_mut(business) {
+                    storage.add(crop.good, stored);
+                }
+                // A full warehouse leaves the rest of the harvest on the field
+                let rest = crop.yield_per_plot - stored;
+                if rest > 0 {
+                    let pos = transform.translation().truncate();
+                    spill_to_stockpile(&mut commands, &storage_config, &stockpiles, &mut storages, business, pos, crop.good, vec![Batch::fresh(crop.good, rest)]);
+                }
+                produced.write(GoodsProduced { workstation: ev.poster, business, good: crop.good, amount: crop.yield_per_plot });
+                plot.state = PlotState::Fallow;
+                plot.growth_days = 0.0;
+            }
+        }
+    }
+}
+
+fn update_plot_visuals(
+    crops: Res<CropDefinitions>,
+    mut plots: Query<(&FarmPlot, &mut Sprite), Changed<FarmPlot>>,
+) {
+    for (plot, mut sprite) in &mut plots {
+        sprite.color = match plot.state {
+            PlotState::Fallow => Color::srgb(0.45, 0.3, 0.15),
+            PlotState::Growing => {
+                let stages = crops.0.get(plot.crop).map_or(1, |c| c.stage_days.len().max(1));
+                let stage = crops.0.get(plot.crop).map_or(0, |c| c.stage(plot.growth_days));
+                // Seedlings are pale, later stages a deeper green
+                let t = stage as f32 / stages as f32;
+                Color::srgb(0.5 - 0.3 * t, 0.6 + 0.1 * t, 0.2)
+            }
+            PlotState::Ripe => Color::srgb(0.85, 0.7, 0.2),
+        };
+    }
+}
@@ -0,0 +1,201 @@
+pub struct FinanceSystems;
+use crate::*;
+use crate::market::{Market, Town};
+use crate::notifications::Notification;
+use crate::ownership::{Factions, Holdings, Owner};
+use crate::production::{Bussiness, EmployedBy, Employees, Good, Storage, Workstation};
+use bevy::prelude::*;
+use bevy::ecs::schedule::common_conditions::on_event;
+use std::collections::HashMap;
+
+impl Plugin for FinanceSystems {
+    fn build(&self, app: &mut App) {
+        app
+            .insert_resource(FinanceConfig::default())
+            .add_event::<SolvencyChanged>()
+            .add_event::<BusinessBankrupt>()
+            .add_systems(
+                Update,
+                (pay_expenses, check_solvency, liquidate_bankrupt, notify_finance_events)
+                    .chain()
+                    .run_if(on_event::<WorldTick>),
+            );
+    }
+}
+
+#[derive(Resource, Debug, Clone, Copy)]
+pub struct FinanceConfig {
+    pub wage_per_worker: i32,
+    pub upkeep_per_workstation: i32,
+    // Consecutive negative ticks before each stage is reached
+    pub shutdown_after: u32,
+    pub liquidate_after: u32,
+    // Fraction of the market price recovered when stock is sold off in liquidation
+    pub liquidation_discount: f32,
+}
+
+impl Default for FinanceConfig {
+    fn default() -> Self {
+        Self {
+            wage_per_worker: 2,
+            upkeep_per_workstation: 1,
+            shutdown_after: 30,
+            liquidate_after: 90,
+            liquidation_discount: 0.5,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SolvencyStage {
+    #[default]
+    Healthy,
+    // Balance is negative
+    Warning,
+    // Negative for `shutdown_after` ticks, production stops
+    ShutDown,
+}
+
+#[derive(Component, Debug, Default)]
+pub struct Solvency {
+    pub stage: SolvencyStage,
+    pub negative_ticks: u32,
+}
+
+#[derive(Event, Debug, Clone, Copy)]
+pub struct SolvencyChanged {
+    pub business: Entity,
+    pub stage: SolvencyStage,
+    pub balance: i32,
+}
+
+// Written before the business is despawned; its workers are already let go
+#[derive(Event, Debug, Clone)]
+pub struct BusinessBankrupt {
+    pub business: Entity,
+    // The business is gone by the time readers see the event
+    pub name: String,
+    pub debt: i32,
+}
+
+fn pay_expenses(
+    config: Res<FinanceConfig>,
+    mut businesses: Query<(Entity, &mut Storage, Option<&Employees>), (With<Bussiness>, Without<Town>)>,
+    workstations: Query<&Owner, With<Workstation>>,
+    mut town: Query<&mut Storage, With<Town>>,
+) {
+    let mut stations_per_business: HashMap<Entity, i32> = HashMap::new();
+    for business in &workstations {
+        *stations_per_business.entry(business.0).or_default() += 1;
+    }
+
+    let mut wages_paid = 0;
+    for (entity, mut storage, employees) in &mut businesses {
+        let workers = employees.map_or(0, |e| e.count()) as i32;
+        let stations = stations_per_business.get(&entity).copied().unwrap_or(0);
+        let wages = workers * config.wage_per_worker;
+        storage.money -= wages + stations * config.upkeep_per_workstation;
+        wages_paid += wages;
+    }
+    // Wages end up as household money to spend on the market
+    if let Ok(mut town_storage) = town.single_mut() {
+        town_storage.money += wages_paid;
+    }
+}
+
+fn business_name(labels: &Query<&EntityLabel>, business: Entity) -> String {
+    labels.get(business).map_or_else(|_| format!("{:?}", business), |l| l.0.clone())
+}
+
+fn check_solvency(
+    mut commands: Commands,
+    config: Res<FinanceConfig>,
+    mut businesses: Query<(Entity, &Storage, Option<&mut Solvency>), With<Bussiness>>,
+    mut changed: EventWriter<SolvencyChanged>,
+) {
+    for (business, storage, solvency) in &mut businesses {
+        let Some(mut solvency) = solvency else {
+            commands.entity(business).insert(Solvency::default());
+            continue;
+        };
+        if storage.money >= 0 {
+            solvency.negative_ticks = 0;
+        } else {
+            solvency.negative_ticks += 1;
+        }
+
+        let stage = match solvency.negative_ticks {
+            0 => SolvencyStage::Healthy,
+            t if t < config.shutdown_after => SolvencyStage::Warning,
+            _ => SolvencyStage::ShutDown,
+        };
+        if stage != solvency.stage {
+            solvency.stage = stage;
+            changed.write(SolvencyChanged { business, stage, balance: storage.money });
+        }
+    }
+}
+
+fn liquidate_bankrupt(
+    mut commands: Commands,
+    config: Res<FinanceConfig>,
+    market: Res<Market>,
+    factions: Res<Factions>,
+    mut businesses: Query<(Entity, &mut Storage, &Solvency, Option<&Employees>, Option<&Holdings>), With<Bussiness>>,
+    workstations: Query<(), With<Workstation>>,
+    mut bankrupt: EventWriter<BusinessBankrupt>,
+    labels: Query<&EntityLabel>,
+) {
+    for (business, mut storage, solvency, employees, holdings) in &mut businesses {
+        if solvency.negative_ticks < config.liquidate_after { continue; }
+
+        // Sell off remaining stock at a discount before giving up
+        for good in Good::ALL {
+            let stock = storage.amount(good);
+            if stock > 0 && storage.take(good, stock) {
+                storage.money += (stock as f32 * market.price(good) * config.liquidation_discount).round() as i32;
+            }
+        }
+        if storage.money >= 0 {
+            continue;
+        }
+
+        if let Some(employees) = employees {
+            for worker in employees.iter() {
+                commands.entity(worker).remove::<EmployedBy>();
+            }
+        }
+        // Workstations close with the business, its buildings fall back to the world
+        if let Some(holdings) = holdings {
+            for owned in holdings.iter() {
+                if workstations.contains(owned) {
+                    commands.entity(owned).despawn();
+                } else {
+                    commands.entity(owned).insert(Owner(factions.world));
+                }
+            }
+        }
+        bankrupt.write(BusinessBankrupt { business, name: business_name(&labels, business), debt: -storage.money });
+        commands.entity(business).despawn();
+    }
+}
+
+fn notify_finance_events(
+    mut changed: EventReader<SolvencyChanged>,
+    mut bankrupt: EventReader<BusinessBankrupt>,
+    labels: Query<&EntityLabel>,
+    mut notifications: EventWriter<Notification>,
+) {
+    for ev in changed.read() {
+        let name = business_name(&labels, ev.business);
+        let notification = match ev.stage {
+            SolvencyStage::Healthy => Notification::info("Finance", format!("{} is out of debt (balance {})", name, ev.balance)),
+            SolvencyStage::Warning => Notification::warning("Finance", format!("{} has run out of money (balance {})", name, ev.balance)),
+            SolvencyStage::ShutDown => Notification::critical("Finance", format!("{} has shut down over its debts (balance {})", name, ev.balance)),
+        };
+        notifications.write(notification.with_target(ev.business));
+    }
+    for ev in bankrupt.read() {
+        notifications.write(Notification::critical("Finance", format!("{} went bankrupt owing {}", ev.name, ev.debt)));
+    }
+}
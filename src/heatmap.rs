@@ -0,0 +1,471 @@
+pub struct HeatmapSystems;
+use crate::*;
+use crate::building::{BuildingKind, ConstructionSite, Footprint};
+use crate::notifications::Notification;
+use crate::production::{Good, Storage};
+use bevy::prelude::*;
+use bevy::ecs::schedule::common_conditions::on_event;
+use bevy::image::ImageSampler;
+use bevy::render::render_asset::RenderAssetUsages;
+use bevy::render::render_resource::{Extent3d, TextureDimension, TextureFormat};
+use bevy::sprite::Anchor;
+use std::collections::{HashMap, VecDeque};
+
+impl Plugin for HeatmapSystems {
+    fn build(&self, app: &mut App) {
+        app
+            .insert_resource(HeatmapConfig::default())
+            .init_resource::<Heatmaps>()
+            .register_heatmap(MovementCostField::default())
+            .register_heatmap(LandValueField::default())
+            .register_heatmap(TrafficField::default())
+            .register_heatmap(PollutionField::default())
+            .register_heatmap(FoodDistanceField)
+            .register_heatmap(FertilityField)
+            .add_systems(Startup, spawn_heatmap_overlay)
+            .add_systems(PostStartup, spawn_heatmap_label)
+            .add_systems(Update, tick_heatmap_fields.run_if(on_event::<WorldTick>))
+            .add_systems(Update, (cycle_heatmap, redraw_heatmap, show_heatmap).chain());
+    }
+}
+
+// A scalar value per grid cell that can be shown as an overlay. Systems add their own with
+// `app.register_heatmap(...)`; the cycle action steps through them in registration order.
+pub trait HeatmapField: Send + Sync + 'static {
+    fn name(&self) -> &str;
+
+    // Runs on every world tick, shown or not, for fields that build up over time
+    fn tick(&mut self, _world: &mut World) {}
+
+    // Fills one value per cell, row by row from the bottom-left; NaN leaves a cell uncoloured
+    fn sample(&mut self, world: &mut World, values: &mut [f32]);
+
+    // Fixed bounds for the colour ramp, otherwise it stretches over the sampled values
+    fn range(&self) -> Option<(f32, f32)> {
+        None
+    }
+}
+
+pub trait HeatmapAppExt {
+    fn register_heatmap(&mut self, field: impl HeatmapField) -> &mut Self;
+}
+
+impl HeatmapAppExt for App {
+    fn register_heatmap(&mut self, field: impl HeatmapField) -> &mut Self {
+        self.init_resource::<Heatmaps>();
+        self.world_mut().resource_mut::<Heatmaps>().fields.push(Box::new(field));
+        self
+    }
+}
+
+#[derive(Resource, Default)]
+pub struct Heatmaps {
+    fields: Vec<Box<dyn HeatmapField>>,
+    // Index into `fields` of the overlay on screen
+    active: Option<usize>,
+    // Set when the shown overlay needs resampling
+    dirty: bool,
+    // Bounds of the last redraw, for the label
+    shown_range: (f32, f32),
+    ticks: u32,
+}
+
+impl Heatmaps {
+    pub fn active_name(&self) -> Option<&str> {
+        self.active.and_then(|i| self.fields.get(i)).map(|f| f.name())
+    }
+}
+
+#[derive(Resource, Debug, Clone, Copy)]
+pub struct HeatmapConfig {
+    pub overlay_alpha: f32,
+    // The shown overlay is resampled every this many ticks
+    pub refresh_ticks: u32,
+}
+
+impl Default for HeatmapConfig {
+    fn default() -> Self {
+        Self {
+            overlay_alpha: 0.55,
+            refresh_ticks: 5,
+        }
+    }
+}
+
+// Adds `strength` at each source cell, fading linearly to nothing `radius` cells away
+fn spread(grid: &WorldGrid, sources: &[(Vec2, f32)], radius: f32, values: &mut [f32]) {
+    let (width, height) = (grid.width() as i32, grid.height() as i32);
+    let reach = radius.ceil() as i32;
+    for (cell, strength) in sources {
+        let (cx, cy) = (cell.x as i32, cell.y as i32);
+        for y in (cy - reach).max(0)..=(cy + reach).min(height - 1) {
+            for x in (cx - reach).max(0)..=(cx + reach).min(width - 1) {
+                let distance = Vec2::new((x - cx) as f32, (y - cy) as f32).length();
+                if distance < radius {
+                    values[(y * width + x) as usize] += strength * (1.0 - distance / radius);
+                }
+            }
+        }
+    }
+}
+
+// Centre cells of finished buildings, optionally only of one kind
+fn building_cells(world: &mut World, kind: Option<BuildingKind>) -> Vec<Vec2> {
+    world
+        .query_filtered::<(&BuildingKind, &Footprint), Without<ConstructionSite>>()
+        .iter(world)
+        .filter(|(k, _)| kind.is_none_or(|kind| **k == kind))
+        .map(|(_, footprint)| (footprint.origin + footprint.size / 2.0).floor())
+        .collect()
+}
+
+fn character_cells(world: &mut World) -> Vec<Vec2> {
+    let positions: Vec<Vec2> = world
+        .query_filtered::<&Transform, With<Health>>()
+        .iter(world)
+        .map(|t| t.translation.truncate())
+        .collect();
+    let grid = world.resource::<WorldGrid>();
+    positions.into_iter().map(|p| grid.world_to_grid(p)).collect()
+}
+
+// How hard each terrain type is to cross
+pub struct MovementCostField {
+    pub costs: HashMap<u8, f32>,
+}
+
+impl Default for MovementCostField {
+    fn default() -> Self {
+        // Ground, buildings, water, farm plots
+        Self { costs: HashMap::from([(0, 1.0), (1, 8.0), (2, 4.0), (3, 1.5)]) }
+    }
+}
+
+impl HeatmapField for MovementCostField {
+    fn name(&self) -> &str { "Movement cost" }
+
+    fn sample(&mut self, world: &mut World, values: &mut [f32]) {
+        let grid = world.resource::<WorldGrid>();
+        for (i, value) in values.iter_mut().enumerate() {
+            let cell = Vec2::new((i as u32 % grid.width()) as f32, (i as u32 / grid.width()) as f32);
+            let terrain = grid.tile(cell).map_or(0, |t| t.terrain_type);
+            *value = self.costs.get(&terrain).copied().unwrap_or(1.0);
+        }
+    }
+}
+
+// Workshops foul the air around them
+pub struct PollutionField {
+    pub per_workshop: f32,
+    pub radius: f32,
+}
+
+impl Default for PollutionField {
+    fn default() -> Self {
+        Self { per_workshop: 1.0, radius: 8.0 }
+    }
+}
+
+impl HeatmapField for PollutionField {
+    fn name(&self) -> &str { "Pollution" }
+
+    fn sample(&mut self, world: &mut World, values: &mut [f32]) {
+        let sources: Vec<(Vec2, f32)> = building_cells(world, Some(BuildingKind::Workshop))
+            .into_iter()
+            .map(|cell| (cell, self.per_workshop))
+            .collect();
+        spread(world.resource::<WorldGrid>(), &sources, self.radius, values);
+    }
+}
+
+// Land near other buildings is worth more, polluted land less
+pub struct LandValueField {
+    pub radius: f32,
+    pub pollution: PollutionField,
+    // Value lost per unit of pollution
+    pub pollution_weight: f32,
+}
+
+impl Default for LandValueField {
+    fn default() -> Self {
+        Self { radius: 10.0, pollution: PollutionField::default(), pollution_weight: 1.5 }
+    }
+}
+
+impl HeatmapField for LandValueField {
+    fn name(&self) -> &str { "Land value" }
+
+    fn sample(&mut self, world: &mut World, values: &mut [f32]) {
+        let neighbours: Vec<(Vec2, f32)> = building_cells(world, None).into_iter().map(|cell| (cell, 1.0)).collect();
+        spread(world.resource::<WorldGrid>(), &neighbours, self.radius, values);
+        let mut pollution = vec![0.0; values.len()];
+        self.pollution.sample(world, &mut pollution);
+        for (value, dirt) in values.iter_mut().zip(pollution) {
+            *value -= dirt * self.pollution_weight;
+        }
+    }
+}
+
+// Characters passing through each cell, fading so it shows recent movement
+pub struct TrafficField {
+    // Share kept from one tick to the next
+    pub decay: f32,
+    counts: Vec<f32>,
+}
+
+impl Default for TrafficField {
+    fn default() -> Self {
+        Self { decay: 0.98, counts: Vec::new() }
+    }
+}
+
+impl HeatmapField for TrafficField {
+    fn name(&self) -> &str { "Traffic" }
+
+    fn tick(&mut self, world: &mut World) {
+        let cells = character_cells(world);
+        let grid = world.resource::<WorldGrid>();
+        let (width, height) = (grid.width(), grid.height());
+        self.counts.resize((width * height) as usize, 0.0);
+        for count in self.counts.iter_mut() {
+            *count *= self.decay;
+        }
+        for cell in cells {
+            if cell.x < 0.0 || cell.y < 0.0 || cell.x as u32 >= width || cell.y as u32 >= height { continue; }
+            self.counts[(cell.y as u32 * width + cell.x as u32) as usize] += 1.0;
+        }
+    }
+
+    fn sample(&mut self, _world: &mut World, values: &mut [f32]) {
+        for (value, count) in values.iter_mut().zip(&self.counts) {
+            *value = *count;
+        }
+    }
+}
+
+// Steps from each cell to the nearest loose food or storage holding food
+pub struct FoodDistanceField;
+
+impl HeatmapField for FoodDistanceField {
+    fn name(&self) -> &str { "Distance to food" }
+
+    fn sample(&mut self, world: &mut World, values: &mut [f32]) {
+        let mut positions: Vec<Vec2> = world
+            .query_filtered::<&Transform, With<Food>>()
+            .iter(world)
+            .map(|t| t.translation.truncate())
+            .collect();
+        positions.extend(
+            world
+                .query::<(&Transform, &Storage)>()
+                .iter(world)
+                .filter(|(_, storage)| storage.amount(Good::Food) > 0)
+                .map(|(t, _)| t.translation.truncate()),
+        );
+        let grid = world.resource::<WorldGrid>();
+        let (width, height) = (grid.width() as i32, grid.height() as i32);
+
+        // Breadth-first from every source at once; cells never reached stay uncoloured
+        values.fill(f32::NAN);
+        let mut queue = VecDeque::new();
+        for cell in positions.into_iter().map(|p| grid.world_to_grid(p)) {
+            let (x, y) = (cell.x as i32, cell.y as i32);
+            if x < 0 || y < 0 || x >= width || y >= height { continue; }
+            let index = (y * width + x) as usize;
+            if values[index].is_nan() {
+                values[index] = 0.0;
+                queue.push_back((x, y));
+            }
+        }
+        while let Some((x, y)) = queue.pop_front() {
+            let steps = values[(y * width + x) as usize] + 1.0;
+            for (nx, ny) in [(x + 1, y), (x - 1, y), (x, y + 1), (x, y - 1)] {
+                if nx < 0 || ny < 0 || nx >= width || ny >= height { continue; }
+                let index = (ny * width + nx) as usize;
+                if values[index].is_nan() {
+                    values[index] = steps;
+                    queue.push_back((nx, ny));
+                }
+            }
+        }
+    }
+}
+
+// Soil fertility as used by crop growth
+pub struct FertilityField;
+
+impl HeatmapField for FertilityField {
+    fn name(&self) -> &str { "Fertility" }
+
+    fn sample(&mut self, world: &mut World, values: &mut [f32]) {
+        let grid = world.resource::<WorldGrid>();
+        for (i, value) in values.iter_mut().enumerate() {
+            let cell = Vec2::new((i as u32 % grid.width()) as f32, (i as u32 / grid.width()) as f32);
+            *value = grid.tile(cell).map_or(f32::NAN, |t| t.fertility);
+        }
+    }
+}
+
+fn tick_heatmap_fields(world: &mut World) {
+    let refresh = world.resource::<HeatmapConfig>().refresh_ticks.max(1);
+    world.resource_scope(|world, mut heatmaps: Mut<Heatmaps>| {
+        for field in heatmaps.fields.iter_mut() {
+            field.tick(world);
+        }
+        heatmaps.ticks += 1;
+        if heatmaps.ticks % refresh == 0 && heatmaps.active.is_some() {
+            heatmaps.dirty = true;
+        }
+    });
+}
+
+// Steps through the registered overlays and back to none
+fn cycle_heatmap(
+    actions: Res<Actions>,
+    mut heatmaps: ResMut<Heatmaps>,
+    mut notifications: EventWriter<Notification>,
+) {
+    if !actions.just_pressed(Action::CycleHeatmap) { return; }
+    let next = heatmaps.active.map_or(0, |i| i + 1);
+    heatmaps.active = (next < heatmaps.fields.len()).then_some(next);
+    heatmaps.dirty = true;
+    let message = match heatmaps.active_name() {
+        Some(name) => format!("Showing {name}"),
+        None => "Hidden".to_string(),
+    };
+    notifications.write(Notification::info("Heatmap", message));
+}
+
+// One pixel per tile, stretched over the whole grid above the zone overlay
+#[derive(Component)]
+struct HeatmapOverlay;
+
+#[derive(Component)]
+struct HeatmapLabel;
+
+fn spawn_heatmap_overlay(
+    mut commands: Commands,
+    mut images: ResMut<Assets<Image>>,
+    grid: Res<WorldGrid>,
+) {
+    let mut image = Image::new_fill(
+        Extent3d { width: grid.width(), height: grid.height(), depth_or_array_layers: 1 },
+        TextureDimension::D2,
+        &[0, 0, 0, 0],
+        TextureFormat::Rgba8UnormSrgb,
+        RenderAssetUsages::default(),
+    );
+    image.sampler = ImageSampler::nearest();
+    let scale = grid.scale() as f32;
+    commands.spawn((
+        Name::new("Heatmap overlay"),
+        HeatmapOverlay,
+        Sprite {
+            image: images.add(image),
+            custom_size: Some(Vec2::new(grid.width() as f32 * scale, grid.height() as f32 * scale)),
+            ..default()
+        },
+        Transform::from_xyz(0.0, 0.0, 0.03),
+        Visibility::Hidden,
+        Pickable::IGNORE,
+    ));
+}
+
+fn spawn_heatmap_label(mut commands: Commands, camera_q: Query<Entity, With<MainCamera>>) {
+    let Ok(camera) = camera_q.single() else { return; };
+    commands.entity(camera).with_children(|cam| {
+        cam.spawn((
+            Name::new("Heatmap Root"),
+            UiLayoutRoot::new_2d(),
+            UiFetchFromCamera::<0>,
+        ))
+        .with_children(|ui| {
+            ui.spawn((
+                Name::new("Heatmap Label"),
+                UiLayout::window()
+                    .anchor(Anchor::TopCenter)
+                    .pos(Rl((50.0, 2.0)))
+                    .pack(),
+                UiTextSize::from(Rh(3.0)),
+                Text2d::new(""),
+                Anchor::TopCenter,
+                Pickable::IGNORE,
+                HeatmapLabel,
+            ));
+        });
+    });
+}
+
+// Blue for the low end, through yellow, to red for the high end
+fn ramp(t: f32, alpha: f32) -> [u8; 4] {
+    let stops = [Vec3::new(0.1, 0.3, 0.9), Vec3::new(0.95, 0.85, 0.2), Vec3::new(0.9, 0.15, 0.1)];
+    let t = t.clamp(0.0, 1.0) * 2.0;
+    let i = (t as usize).min(1);
+    let c = stops[i].lerp(stops[i + 1], t - i as f32);
+    Color::srgba(c.x, c.y, c.z, alpha).to_srgba().to_u8_array()
+}
+
+// Resamples the shown field into the overlay image when marked dirty
+fn redraw_heatmap(world: &mut World) {
+    let alpha = world.resource::<HeatmapConfig>().overlay_alpha;
+    world.resource_scope(|world, mut heatmaps: Mut<Heatmaps>| {
+        if !heatmaps.dirty { return; }
+        heatmaps.dirty = false;
+        let Some(active) = heatmaps.active else { return; };
+        let (width, height) = {
+            let grid = world.resource::<WorldGrid>();
+            (grid.width(), grid.height())
+        };
+        let mut values = vec![0.0; (width * height) as usize];
+        let field = &mut heatmaps.fields[active];
+        field.sample(world, &mut values);
+        let (low, high) = field.range().unwrap_or_else(|| {
+            values
+                .iter()
+                .filter(|v| v.is_finite())
+                .fold((f32::MAX, f32::MIN), |(lo, hi), v| (lo.min(*v), hi.max(*v)))
+        });
+        heatmaps.shown_range = (low, high);
+        let span = (high - low).max(f32::EPSILON);
+
+        let Ok(sprite) = world.query_filtered::<&Sprite, With<HeatmapOverlay>>().single(world) else { return; };
+        let handle = sprite.image.clone();
+        let mut images = world.resource_mut::<Assets<Image>>();
+        let Some(data) = images.get_mut(&handle).and_then(|image| image.data.as_mut()) else { return; };
+        for y in 0..height {
+            for x in 0..width {
+                let value = values[(y * width + x) as usize];
+                // Image rows run top-down, grid rows bottom-up
+                let pixel = (((height - 1 - y) * width + x) * 4) as usize;
+                let color = if value.is_finite() { ramp((value - low) / span, alpha) } else { [0, 0, 0, 0] };
+                data[pixel..pixel + 4].copy_from_slice(&color);
+            }
+        }
+    });
+}
+
+fn show_heatmap(
+    heatmaps: Res<Heatmaps>,
+    mut overlays: Query<&mut Visibility, With<HeatmapOverlay>>,
+    mut labels: Query<&mut Text2d, With<HeatmapLabel>>,
+) {
+    if !heatmaps.is_changed() { return; }
+    for mut visibility in &mut overlays {
+        let wanted = if heatmaps.active.is_some() { Visibility::Inherited } else { Visibility::Hidden };
+        if *visibility != wanted {
+            *visibility = wanted;
+        }
+    }
+    let text = match heatmaps.active_name() {
+        Some(name) if heatmaps.shown_range.0 <= heatmaps.shown_range.1 => {
+            format!("{name}: {:.1} (blue) to {:.1} (red)", heatmaps.shown_range.0, heatmaps.shown_range.1)
+        }
+        Some(name) => format!("{name}: no data"),
+        None => String::new(),
+    };
+    for mut label in &mut labels {
+        if label.0 != text {
+            label.0 = text.clone();
+        }
+    }
+}
@@ -0,0 +1,529 @@
+pub struct InputSystems;
+use crate::*;
+use crate::notifications::Notification;
+use bevy::input::InputSystem;
+use bevy::sprite::Anchor;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+const INPUT_CONFIG_PATH: &str = "assets/config/input.ron";
+// Rebinds made in game, layered over the shipped bindings above
+const USER_INPUT_CONFIG_PATH: &str = "settings/input.ron";
+
+impl Plugin for InputSystems {
+    fn build(&self, app: &mut App) {
+        app
+            .insert_resource(InputBindings::load(INPUT_CONFIG_PATH, USER_INPUT_CONFIG_PATH))
+            .init_resource::<Actions>()
+            .init_resource::<Rebinding>()
+            .add_systems(Startup, warn_binding_conflicts)
+            .add_systems(PreUpdate, update_actions.after(InputSystem))
+            .add_systems(Update, (toggle_bindings_screen, capture_rebinding, update_binding_rows).chain());
+    }
+}
+
+// Where an action can fire. Actions only conflict when their contexts can be active together.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ActionContext {
+    Global,
+    // Outside the building state
+    World,
+    Building,
+}
+
+impl ActionContext {
+    fn active(&self, state: &GameControlState) -> bool {
+        match self {
+            ActionContext::Global => true,
+            ActionContext::World => *state == GameControlState::Default,
+            ActionContext::Building => *state == GameControlState::Building,
+        }
+    }
+
+    fn overlaps(&self, other: ActionContext) -> bool {
+        *self == ActionContext::Global || other == ActionContext::Global || *self == other
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum Action {
+    ToggleBuildMode,
+    Cancel,
+    Pause,
+    PanUp,
+    PanDown,
+    PanLeft,
+    PanRight,
+    PanDrag,
+    ZoomIn,
+    ZoomOut,
+    Follow,
+    SaveBookmark,
+    Bookmark1,
+    Bookmark2,
+    Bookmark3,
+    Bookmark4,
+    Bookmark5,
+    Bookmark6,
+    Bookmark7,
+    Bookmark8,
+    Bookmark9,
+    Select,
+    Place,
+    Rotate,
+    Demolish,
+    NextBusiness,
+    ToggleCharts,
+    ToggleZoneOverlay,
+    CycleHeatmap,
+    ToggleNotifications,
+    ToggleBindings,
+    ExportMetrics,
+    PriorityBuild,
+    PriorityHaul,
+    PriorityOperate,
+    PriorityHarvest,
+}
+
+impl Action {
+    pub const ALL: [Action; 36] = [
+        Action::ToggleBuildMode, Action::Cancel, Action::Pause,
+        Action::PanUp, Action::PanDown, Action::PanLeft, Action::PanRight, Action::PanDrag, Action::ZoomIn, Action::ZoomOut,
+        Action::Follow, Action::SaveBookmark,
+        Action::Bookmark1, Action::Bookmark2, Action::Bookmark3, Action::Bookmark4, Action::Bookmark5,
+        Action::Bookmark6, Action::Bookmark7, Action::Bookmark8, Action::Bookmark9,
+        Action::Select, Action::Place, Action::Rotate, Action::Demolish,
+        Action::NextBusiness, Action::ToggleCharts, Action::ToggleZoneOverlay, Action::CycleHeatmap, Action::ToggleNotifications, Action::ToggleBindings, Action::ExportMetrics,
+        Action::PriorityBuild, Action::PriorityHaul, Action::PriorityOperate, Action::PriorityHarvest,
+    ];
+
+    // Jump to a camera bookmark, or store one while `SaveBookmark` is held
+    pub const BOOKMARKS: [Action; 9] = [
+        Action::Bookmark1, Action::Bookmark2, Action::Bookmark3, Action::Bookmark4, Action::Bookmark5,
+        Action::Bookmark6, Action::Bookmark7, Action::Bookmark8, Action::Bookmark9,
+    ];
+
+    pub fn context(&self) -> ActionContext {
+        match self {
+            Action::Select => ActionContext::World,
+            Action::Place | Action::Rotate | Action::Demolish => ActionContext::Building,
+            _ => ActionContext::Global,
+        }
+    }
+
+    fn default_bindings(&self) -> Vec<Binding> {
+        use Binding::{Key, Mouse};
+        match self {
+            Action::ToggleBuildMode => vec![Key(KeyCode::KeyB)],
+            Action::Cancel => vec![Key(KeyCode::Escape)],
+            Action::Pause => vec![Key(KeyCode::Space)],
+            Action::PanUp => vec![Key(KeyCode::KeyW), Key(KeyCode::ArrowUp)],
+            Action::PanDown => vec![Key(KeyCode::KeyS), Key(KeyCode::ArrowDown)],
+            Action::PanLeft => vec![Key(KeyCode::KeyA), Key(KeyCode::ArrowLeft)],
+            Action::PanRight => vec![Key(KeyCode::KeyD), Key(KeyCode::ArrowRight)],
+            Action::PanDrag => vec![Mouse(MouseButton::Middle), Mouse(MouseButton::Right)],
+            Action::ZoomIn => vec![Key(KeyCode::Equal), Key(KeyCode::NumpadAdd)],
+            Action::ZoomOut => vec![Key(KeyCode::Minus), Key(KeyCode::NumpadSubtract)],
+            Action::Follow => vec![Key(KeyCode::KeyF)],
+            Action::SaveBookmark => vec![Key(KeyCode::ControlLeft), Key(KeyCode::ControlRight)],
+            Action::Bookmark1 => vec![Key(KeyCode::Digit1)],
+            Action::Bookmark2 => vec![Key(KeyCode::Digit2)],
+            Action::Bookmark3 => vec![Key(KeyCode::Digit3)],
+            Action::Bookmark4 => vec![Key(KeyCode::Digit4)],
+            Action::Bookmark5 => vec![Key(KeyCode::Digit5)],
+            Action::Bookmark6 => vec![Key(KeyCode::Digit6)],
+            Action::Bookmark7 => vec![Key(KeyCode::Digit7)],
+            Action::Bookmark8 => vec![Key(KeyCode::Digit8)],
+            Action::Bookmark9 => vec![Key(KeyCode::Digit9)],
+            Action::Select => vec![Mouse(MouseButton::Left)],
+            Action::Place => vec![Mouse(MouseButton::Left)],
+            Action::Rotate => vec![Key(KeyCode::KeyR)],
+            Action::Demolish => vec![Key(KeyCode::Delete)],
+            Action::NextBusiness => vec![Key(KeyCode::Tab)],
+            Action::ToggleCharts => vec![Key(KeyCode::KeyG)],
+            Action::ToggleZoneOverlay => vec![Key(KeyCode::KeyZ)],
+            Action::CycleHeatmap => vec![Key(KeyCode::KeyH)],
+            Action::ToggleNotifications => vec![Key(KeyCode::KeyN)],
+            Action::ToggleBindings => vec![Key(KeyCode::F10)],
+            Action::ExportMetrics => vec![Key(KeyCode::F9)],
+            Action::PriorityBuild => vec![Key(KeyCode::F1)],
+            Action::PriorityHaul => vec![Key(KeyCode::F2)],
+            Action::PriorityOperate => vec![Key(KeyCode::F3)],
+            Action::PriorityHarvest => vec![Key(KeyCode::F4)],
+        }
+    }
+}
+
+// Keys that can be bound, named as in the config file
+const BINDABLE_KEYS: &[KeyCode] = &[
+    KeyCode::KeyA, KeyCode::KeyB, KeyCode::KeyC, KeyCode::KeyD, KeyCode::KeyE, KeyCode::KeyF, KeyCode::KeyG,
+    KeyCode::KeyH, KeyCode::KeyI, KeyCode::KeyJ, KeyCode::KeyK, KeyCode::KeyL, KeyCode::KeyM, KeyCode::KeyN,
+    KeyCode::KeyO, KeyCode::KeyP, KeyCode::KeyQ, KeyCode::KeyR, KeyCode::KeyS, KeyCode::KeyT, KeyCode::KeyU,
+    KeyCode::KeyV, KeyCode::KeyW, KeyCode::KeyX, KeyCode::KeyY, KeyCode::KeyZ,
+    KeyCode::Digit0, KeyCode::Digit1, KeyCode::Digit2, KeyCode::Digit3, KeyCode::Digit4,
+    KeyCode::Digit5, KeyCode::Digit6, KeyCode::Digit7, KeyCode::Digit8, KeyCode::Digit9,
+    KeyCode::F1, KeyCode::F2, KeyCode::F3, KeyCode::F4, KeyCode::F5, KeyCode::F6,
+    KeyCode::F7, KeyCode::F8, KeyCode::F9, KeyCode::F10, KeyCode::F11, KeyCode::F12,
+    KeyCode::ArrowUp, KeyCode::ArrowDown, KeyCode::ArrowLeft, KeyCode::ArrowRight,
+    KeyCode::Space, KeyCode::Escape, KeyCode::Tab, KeyCode::Enter, KeyCode::Backspace,
+    KeyCode::Delete, KeyCode::Insert, KeyCode::Home, KeyCode::End, KeyCode::PageUp, KeyCode::PageDown,
+    KeyCode::Equal, KeyCode::Minus, KeyCode::Comma, KeyCode::Period, KeyCode::Slash, KeyCode::Semicolon,
+    KeyCode::BracketLeft, KeyCode::BracketRight, KeyCode::Backquote,
+    KeyCode::NumpadAdd, KeyCode::NumpadSubtract,
+    KeyCode::ShiftLeft, KeyCode::ShiftRight, KeyCode::ControlLeft, KeyCode::ControlRight, KeyCode::AltLeft, KeyCode::AltRight,
+];
+
+const BINDABLE_BUTTONS: &[MouseButton] = &[MouseButton::Left, MouseButton::Right, MouseButton::Middle, MouseButton::Back, MouseButton::Forward];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Binding {
+    Key(KeyCode),
+    Mouse(MouseButton),
+}
+
+impl Binding {
+    // "KeyB", "F1", "MouseLeft", ...
+    fn parse(name: &str) -> Option<Binding> {
+        if let Some(button) = name.strip_prefix("Mouse") {
+            return BINDABLE_BUTTONS.iter().find(|b| format!("{b:?}") == button).map(|b| Binding::Mouse(*b));
+        }
+        BINDABLE_KEYS.iter().find(|k| format!("{k:?}") == name).map(|k| Binding::Key(*k))
+    }
+
+    fn pressed(&self, keys: &ButtonInput<KeyCode>, buttons: &ButtonInput<MouseButton>) -> bool {
+        match self {
+            Binding::Key(key) => keys.pressed(*key),
+            Binding::Mouse(button) => buttons.pressed(*button),
+        }
+    }
+
+    fn just_pressed(&self, keys: &ButtonInput<KeyCode>, buttons: &ButtonInput<MouseButton>) -> bool {
+        match self {
+            Binding::Key(key) => keys.just_pressed(*key),
+            Binding::Mouse(button) => buttons.just_pressed(*button),
+        }
+    }
+}
+
+impl std::fmt::Display for Binding {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Binding::Key(key) => write!(f, "{key:?}"),
+            Binding::Mouse(button) => write!(f, "Mouse{button:?}"),
+        }
+    }
+}
+
+// Keys and buttons behind every action, read from `assets/config/input.ron` and the user's rebinds
+#[derive(Resource, Debug, Clone)]
+pub struct InputBindings(pub BTreeMap<Action, Vec<Binding>>);
+
+impl Default for InputBindings {
+    fn default() -> Self {
+        Self(Action::ALL.iter().map(|a| (*a, a.default_bindings())).collect())
+    }
+}
+
+impl InputBindings {
+    // Shipped bindings with the user's rebinds on top; actions missing from both keep their default bindings
+    pub fn load(path: &str, user_path: &str) -> Self {
+        let mut bindings = Self::default();
+        bindings.apply_file(path);
+        if std::path::Path::new(user_path).exists() {
+            bindings.apply_file(user_path);
+        }
+        bindings
+    }
+
+    fn apply_file(&mut self, path: &str) {
+        let Ok(text) = std::fs::read_to_string(path) else {
+            warn!("[input] No config at {path}, using default bindings");
+            return;
+        };
+        let names: BTreeMap<Action, Vec<String>> = match ron::from_str(&text) {
+            Ok(names) => names,
+            Err(e) => {
+                warn!("[input] Failed to parse {path}: {e}, using default bindings");
+                return;
+            }
+        };
+        for (action, names) in names {
+            let parsed = names
+                .iter()
+                .filter_map(|name| {
+                    let binding = Binding::parse(name);
+                    if binding.is_none() { warn!("[input] Unknown binding {name:?} for {action:?}"); }
+                    binding
+                })
+                .collect();
+            self.0.insert(action, parsed);
+        }
+    }
+
+    // Writes only the actions that differ from the shipped bindings in `path`, which stays untouched
+    pub fn save(&self, path: &str, user_path: &str) -> Result<(), String> {
+        let mut shipped = Self::default();
+        shipped.apply_file(path);
+        let names: BTreeMap<Action, Vec<String>> = self.0
+            .iter()
+            .filter(|(action, bindings)| shipped.get(**action) != bindings.as_slice())
+            .map(|(action, bindings)| (*action, bindings.iter().map(|b| b.to_string()).collect()))
+            .collect();
+        let text = ron::ser::to_string_pretty(&names, ron::ser::PrettyConfig::default()).map_err(|e| e.to_string())?;
+        if let Some(dir) = std::path::Path::new(user_path).parent() {
+            std::fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+        }
+        std::fs::write(user_path, text).map_err(|e| e.to_string())
+    }
+
+    pub fn get(&self, action: Action) -> &[Binding] {
+        self.0.get(&action).map_or(&[][..], |b| b.as_slice())
+    }
+
+    // Bindings shared by two actions that can be active at the same time
+    pub fn conflicts(&self) -> Vec<(Binding, Action, Action)> {
+        let mut found = Vec::new();
+        let actions: Vec<(&Action, &Vec<Binding>)> = self.0.iter().collect();
+        for (i, (a, a_bindings)) in actions.iter().enumerate() {
+            for (b, b_bindings) in &actions[i + 1..] {
+                if !a.context().overlaps(b.context()) { continue; }
+                for binding in a_bindings.iter().filter(|x| b_bindings.contains(x)) {
+                    found.push((*binding, **a, **b));
+                }
+            }
+        }
+        found
+    }
+}
+
+// Actions held, pressed and released this frame, for the current control state
+#[derive(Resource, Debug, Default)]
+pub struct Actions {
+    pressed: HashSet<Action>,
+    just_pressed: HashSet<Action>,
+    just_released: HashSet<Action>,
+}
+
+impl Actions {
+    pub fn pressed(&self, action: Action) -> bool {
+        self.pressed.contains(&action)
+    }
+
+    pub fn just_pressed(&self, action: Action) -> bool {
+        self.just_pressed.contains(&action)
+    }
+
+    pub fn just_released(&self, action: Action) -> bool {
+        self.just_released.contains(&action)
+    }
+}
+
+fn update_actions(
+    keys: Res<ButtonInput<KeyCode>>,
+    buttons: Res<ButtonInput<MouseButton>>,
+    bindings: Res<InputBindings>,
+    state: Res<State<GameControlState>>,
+    rebinding: Res<Rebinding>,
+    mut actions: ResMut<Actions>,
+) {
+    let previous = std::mem::take(&mut actions.pressed);
+    actions.just_pressed.clear();
+    // While a new binding is being captured nothing else reacts to input
+    if rebinding.action.is_none() {
+        for (action, action_bindings) in &bindings.0 {
+            if !action.context().active(state.get()) { continue; }
+            if action_bindings.iter().any(|b| b.pressed(&keys, &buttons)) {
+                actions.pressed.insert(*action);
+            }
+            if action_bindings.iter().any(|b| b.just_pressed(&keys, &buttons)) {
+                actions.just_pressed.insert(*action);
+            }
+        }
+    }
+    actions.just_released = previous.difference(&actions.pressed).copied().collect();
+}
+
+// Action waiting for its new binding; `armed` skips the click that started the capture
+#[derive(Resource, Debug, Default)]
+struct Rebinding {
+    open: bool,
+    action: Option<Action>,
+    armed: bool,
+    hovered: bool,
+}
+
+#[derive(Component)]
+struct BindingsUi;
+
+#[derive(Component)]
+struct BindingRow(Action);
+
+#[derive(Component)]
+struct BindingLabel(Action);
+
+fn toggle_bindings_screen(
+    mut commands: Commands,
+    actions: Res<Actions>,
+    mut rebinding: ResMut<Rebinding>,
+    mut hover: ResMut<UiBlockHoverCount>,
+    camera_q: Query<Entity, With<MainCamera>>,
+    ui_query: Query<Entity, With<BindingsUi>>,
+) {
+    if !actions.just_pressed(Action::ToggleBindings) { return; }
+    rebinding.open = !rebinding.open;
+    rebinding.action = None;
+
+    if !rebinding.open {
+        for e in &ui_query {
+            commands.entity(e).despawn();
+        }
+        // The window may vanish under the cursor without a Pointer<Out>
+        if rebinding.hovered && hover.0 > 0 { hover.0 -= 1; }
+        rebinding.hovered = false;
+        return;
+    }
+
+    let Ok(camera) = camera_q.single() else { return; };
+    let row_height = 90.0 / Action::ALL.len() as f32;
+    commands.entity(camera).with_children(|cam| {
+        cam.spawn((
+            Name::new("Bindings Root"),
+            UiLayoutRoot::new_2d(),
+            UiFetchFromCamera::<0>,
+            BindingsUi,
+        ))
+        .with_children(|ui| {
+            ui.spawn((
+                Name::new("Bindings Background"),
+                UiLayout::window()
+                    .anchor(Anchor::Center)
+                    .pos(Rl((50.0, 50.0)))
+                    .size(Rl((40.0, 85.0)))
+                    .pack(),
+                Sprite::from_color(Color::srgba(0.08, 0.08, 0.1, 0.9), Vec2::new(50.0, 50.0)),
+            ))
+            .observe(|_: Trigger<Pointer<Over>>, mut cnt: ResMut<UiBlockHoverCount>, mut rebinding: ResMut<Rebinding>| {
+                cnt.0 += 1;
+                rebinding.hovered = true;
+            })
+            .observe(|_: Trigger<Pointer<Out>>, mut cnt: ResMut<UiBlockHoverCount>, mut rebinding: ResMut<Rebinding>| {
+                if cnt.0 > 0 { cnt.0 -= 1; }
+                rebinding.hovered = false;
+            })
+            .with_children(|ui| {
+                ui.spawn((
+                    Name::new("Bindings Title"),
+                    UiLayout::window().anchor(Anchor::TopLeft).pos(Rl((3.0, 2.0))).pack(),
+                    UiTextSize::from(Rh(3.0)),
+                    Text2d::new("Click an action, then press its new key"),
+                    Anchor::TopLeft,
+                ));
+                for (i, action) in Action::ALL.into_iter().enumerate() {
+                    ui.spawn((
+                        Name::new(format!("Binding {action:?}")),
+                        UiLayout::window()
+                            .pos(Rl((3.0, 8.0 + i as f32 * row_height)))
+                            .size(Rl((94.0, row_height * 0.9)))
+                            .pack(),
+                        Sprite::from_color(Color::srgba(0.3, 0.3, 0.35, 1.0), Vec2::new(50.0, 50.0)),
+                        OnHoverSetCursor::new(SystemCursorIcon::Pointer),
+                        BindingRow(action),
+                    ))
+                    .observe(move |_: Trigger<Pointer<Click>>, mut rebinding: ResMut<Rebinding>| {
+                        rebinding.action = Some(action);
+                        rebinding.armed = false;
+                    })
+                    .with_children(|ui| {
+                        ui.spawn((
+                            UiLayout::window().anchor(Anchor::CenterLeft).pos(Rl((2.0, 50.0))).pack(),
+                            UiTextSize::from(Rh(60.0)),
+                            Text2d::new(""),
+                            Anchor::CenterLeft,
+                            Pickable::IGNORE,
+                            BindingLabel(action),
+                        ));
+                    });
+                }
+            });
+        });
+    });
+}
+
+fn conflict_warnings(bindings: &InputBindings) -> Vec<Notification> {
+    bindings
+        .conflicts()
+        .into_iter()
+        .map(|(binding, a, b)| Notification::warning("Input", format!("{} is bound to both {:?} and {:?}", binding, a, b)))
+        .collect()
+}
+
+fn warn_binding_conflicts(bindings: Res<InputBindings>, mut notifications: EventWriter<Notification>) {
+    notifications.write_batch(conflict_warnings(&bindings));
+}
+
+// Replaces the first binding of the chosen action with the next key or button pressed
+fn capture_rebinding(
+    keys: Res<ButtonInput<KeyCode>>,
+    buttons: Res<ButtonInput<MouseButton>>,
+    mut rebinding: ResMut<Rebinding>,
+    mut bindings: ResMut<InputBindings>,
+    mut notifications: EventWriter<Notification>,
+) {
+    let Some(action) = rebinding.action else { return; };
+    if !rebinding.armed {
+        rebinding.armed = true;
+        return;
+    }
+    if keys.just_pressed(KeyCode::Escape) {
+        rebinding.action = None;
+        return;
+    }
+    let pressed = keys
+        .get_just_pressed()
+        .find(|k| BINDABLE_KEYS.contains(*k))
+        .map(|k| Binding::Key(*k))
+        .or_else(|| buttons.get_just_pressed().find(|b| BINDABLE_BUTTONS.contains(*b)).map(|b| Binding::Mouse(*b)));
+    let Some(binding) = pressed else { return; };
+
+    let list = bindings.0.entry(action).or_default();
+    match list.first_mut() {
+        Some(first) => *first = binding,
+        None => list.push(binding),
+    }
+    rebinding.action = None;
+    notifications.write(Notification::info("Input", format!("{:?} bound to {}", action, binding)));
+    notifications.write_batch(conflict_warnings(&bindings));
+    if let Err(e) = bindings.save(INPUT_CONFIG_PATH, USER_INPUT_CONFIG_PATH) {
+        notifications.write(Notification::warning("Input", format!("Failed to save bindings to {USER_INPUT_CONFIG_PATH}: {e}")));
+    }
+}
+
+// Row text lists the bindings; conflicting rows turn red, the one being rebound yellow
+fn update_binding_rows(
+    bindings: Res<InputBindings>,
+    rebinding: Res<Rebinding>,
+    mut rows: Query<(&BindingRow, &mut Sprite)>,
+    mut labels: Query<(&BindingLabel, &mut Text2d)>,
+) {
+    if !rebinding.open || !(bindings.is_changed() || rebinding.is_changed()) { return; }
+    let conflicts = bindings.conflicts();
+    let conflicting = |action: Action| conflicts.iter().any(|(_, a, b)| *a == action || *b == action);
+
+    for (label, mut text) in &mut labels {
+        let keys = if rebinding.action == Some(label.0) {
+            "press a key, Escape to cancel".to_string()
+        } else {
+            bindings.get(label.0).iter().map(|b| b.to_string()).collect::<Vec<_>>().join(", ")
+        };
+        text.0 = format!("{:?}: {}", label.0, keys);
+    }
+    for (row, mut sprite) in &mut rows {
+        sprite.color = if rebinding.action == Some(row.0) {
+            Color::srgba(0.6, 0.55, 0.1, 1.0)
+        } else if conflicting(row.0) {
+            Color::srgba(0.6, 0.15, 0.15, 1.0)
+        } else {
+            Color::srgba(0.3, 0.3, 0.35, 1.0)
+        };
+    }
+}
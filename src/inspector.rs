@@ -0,0 +1,120 @@
+pub struct InspectorSystems;
+use crate::*;
+use crate::building::{BuildingKind, ConstructionSite};
+use crate::jobs::{CurrentJob, JobBoard, Workers};
+use crate::ownership::Owner;
+use crate::production::EmployedBy;
+use crate::skills::{Skill, Skills};
+use bevy::prelude::*;
+
+impl Plugin for InspectorSystems {
+    fn build(&self, app: &mut App) {
+        app
+            .init_resource::<Selection>()
+            .add_systems(Update, select_entity.run_if(in_state(GameControlState::Default)))
+            .add_systems(Update, show_inspector);
+    }
+}
+
+// Character or building picked by clicking in the default control state
+#[derive(Resource, Default, Debug, Clone, Copy)]
+pub struct Selection {
+    pub entity: Option<Entity>,
+}
+
+#[derive(Component)]
+struct InspectorText;
+
+fn select_entity(
+    actions: Res<Actions>,
+    mut events: EventReader<CursorWorldEvent>,
+    over_ui: Res<UiBlockHoverCount>,
+    grid: Res<WorldGrid>,
+    mut selection: ResMut<Selection>,
+    characters: Query<(Entity, &Transform), With<Health>>,
+    buildings: Query<(Entity, &Transform, &BuildingKind), With<Building>>,
+) {
+    if actions.just_pressed(Action::Cancel) {
+        selection.entity = None;
+    }
+    let Some(cursor) = events.read().last().copied() else { return; };
+    if !actions.just_pressed(Action::Select) || over_ui.0 > 0 { return; }
+
+    let pick_radius = 8.0;
+    let character = characters
+        .iter()
+        .map(|(e, t)| (e, t.translation.truncate().distance(cursor.world)))
+        .filter(|(_, d)| *d <= pick_radius)
+        .min_by(|a, b| a.1.total_cmp(&b.1))
+        .map(|(e, _)| e);
+    let building = || buildings
+        .iter()
+        .find(|(_, t, kind)| {
+            let half = kind.size() * grid.scale() as f32 / 2.0;
+            let offset = (cursor.world - t.translation.truncate()).abs();
+            offset.x <= half.x && offset.y <= half.y
+        })
+        .map(|(e, _, _)| e);
+
+    selection.entity = character.or_else(building);
+}
+
+fn show_inspector(
+    mut commands: Commands,
+    selection: Res<Selection>,
+    board: Res<JobBoard>,
+    labels: Query<&EntityLabel>,
+    characters: Query<(&Health, &Hunger, &Thirst, &Sleep, &Activity, Option<&CurrentJob>, Option<&EmployedBy>, Option<&Skills>)>,
+    buildings: Query<(&BuildingKind, Option<&ConstructionSite>, Option<&Workers>, Option<&Owner>)>,
+    mut text_query: Query<(Entity, &mut Text), With<InspectorText>>,
+) {
+    let name = |e: Entity| labels.get(e).map(|l| l.0.clone()).unwrap_or_else(|_| format!("{:?}", e));
+    let lines: Option<Vec<String>> = selection.entity.and_then(|entity| {
+        if let Ok((health, hunger, thirst, sleep, activity, job, employer, skills)) = characters.get(entity) {
+            let mut lines = vec![
+                name(entity),
+                format!("Health {:.0}  Hunger {:.0}  Thirst {:.0}  Sleep {:.0}", health.0, hunger.value, thirst.value, sleep.value),
+                format!("Activity: {:?}", activity),
+                format!("Employer: {}", employer.map_or("none".to_string(), |e| name(e.0))),
+                format!("Job: {}", job.and_then(|j| board.get(j.id)).map_or("none", |j| j.category.name())),
+            ];
+            if let Some(skills) = skills {
+                let mut ranked: Vec<Skill> = Skill::ALL.to_vec();
+                ranked.sort_by(|a, b| skills.level(*b).total_cmp(&skills.level(*a)));
+                lines.extend(ranked.iter().map(|s| format!("  {} {:.1}", s.name(), skills.level(*s))));
+            }
+            Some(lines)
+        } else if let Ok((kind, construction, workers, owner)) = buildings.get(entity) {
+            Some(vec![
+                format!("{} ({:?})", name(entity), kind),
+                construction.map_or("Complete".to_string(), |c| format!("Under construction ({} ticks of work)", c.work_ticks)),
+                format!("Workers: {}", workers.map_or(0, |w| w.count())),
+                format!("Owner: {}", owner.map_or("none".to_string(), |o| name(o.0))),
+            ])
+        } else {
+            None
+        }
+    });
+
+    match (lines, text_query.single_mut()) {
+        (Some(lines), Ok((_, mut text))) => text.0 = lines.join("\n"),
+        (Some(lines), Err(_)) => {
+            commands.spawn((
+                Node {
+                    position_type: PositionType::Absolute,
+                    top: Val::Percent(30.0),
+                    right: Val::Px(10.0),
+                    padding: UiRect::all(Val::Px(6.0)),
+                    ..Default::default()
+                },
+                BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.6)),
+                Text::new(lines.join("\n")),
+                TextFont { font_size: 14.0, ..default() },
+                TextColor(Color::WHITE),
+                InspectorText,
+            ));
+        }
+        (None, Ok((entity, _))) => commands.entity(entity).despawn(),
+        (None, Err(_)) => {}
+    }
+}
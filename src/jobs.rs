@@ -0,0 +1,498 @@
+pub struct JobSystems;
+use crate::*;
+use crate::building::ConstructionSite;
+use crate::logistics::{DropOff, Hauler};
+use crate::needs::CharacterDied;
+use crate::population::CharacterEmigrated;
+use crate::ownership::Owner;
+use crate::production::{Batch, EmployedBy, Good, Operational, Storage, WorkerSpots, Workstation};
+use crate::skills::{Skill, SkillConfig, Skills};
+use crate::storage::{spill_to_stockpile, GroundStockpile, StorageCapacity, StorageConfig};
+use bevy::prelude::*;
+use bevy::ecs::schedule::common_conditions::on_event;
+use std::collections::HashMap;
+
+impl Plugin for JobSystems {
+    fn build(&self, app: &mut App) {
+        app
+            .insert_resource(JobConfig::default())
+            .insert_resource(JobPriorities::default())
+            .init_resource::<JobBoard>()
+            .add_event::<JobCompleted>()
+            .add_event::<JobFailed>()
+            .add_systems(
+                Update,
+                (drop_orphaned_jobs, post_construction_jobs, post_operate_jobs, release_jobs_of_departed, claim_jobs, progress_work)
+                    .chain()
+                    .run_if(on_event::<WorldTick>),
+            )
+            .add_systems(Update, (execute_jobs, finish_construction).chain())
+            .add_systems(Update, (adjust_job_priorities, show_job_priorities));
+    }
+}
+
+#[derive(Resource, Debug, Clone, Copy)]
+pub struct JobConfig {
+    // How close an agent must be to count as arrived
+    pub reach: f32,
+    // Distance in world units that costs as much as one unit of priority
+    pub distance_per_priority: f32,
+    // Ticks of a single workstation shift
+    pub shift_ticks: u32,
+    // Carry weight of agents without a `Hauler` component
+    pub base_carry_weight: f32,
+}
+
+impl Default for JobConfig {
+    fn default() -> Self {
+        Self {
+            reach: 10.0,
+            distance_per_priority: 50.0,
+            shift_ticks: 12,
+            base_carry_weight: 5.0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum JobCategory {
+    Build,
+    Haul,
+    Operate,
+    Harvest,
+}
+
+impl JobCategory {
+    pub const ALL: [JobCategory; 4] = [JobCategory::Build, JobCategory::Haul, JobCategory::Operate, JobCategory::Harvest];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            JobCategory::Build => "Build",
+            JobCategory::Haul => "Haul",
+            JobCategory::Operate => "Operate",
+            JobCategory::Harvest => "Harvest",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Task {
+    // Walk to the target's position
+    MoveTo(Entity),
+    // Take up to `quantity` from the target's `Storage`, limited by carry weight
+    PickUp { from: Entity, good: Good, quantity: i32 },
+    // Deliver everything carried to the target, or the storage behind its `DropOff`
+    DropOff { to: Entity },
+    // Stay at the target for `ticks` world ticks
+    Work { target: Entity, ticks: u32 },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct JobId(u64);
+
+#[derive(Debug, Clone)]
+pub struct Job {
+    pub category: JobCategory,
+    pub priority: i32,
+    // Skill practised by the job and the minimum level needed to take it
+    pub required_skill: Option<(Skill, f32)>,
+    // Only employees of this business may take the job
+    pub employer: Option<Entity>,
+    // Entity that posted the job, e.g. the workstation or construction site
+    pub poster: Entity,
+    pub tasks: Vec<Task>,
+    pub claimed_by: Option<Entity>,
+}
+
+impl Job {
+    pub fn new(category: JobCategory, priority: i32, poster: Entity, tasks: Vec<Task>) -> Self {
+        Self { category, priority, required_skill: None, employer: None, poster, tasks, claimed_by: None }
+    }
+
+    pub fn with_employer(mut self, employer: Entity) -> Self {
+        self.employer = Some(employer);
+        self
+    }
+
+    pub fn with_skill(mut self, skill: Skill, level: f32) -> Self {
+        self.required_skill = Some((skill, level));
+        self
+    }
+
+    // Where the job starts, used to weigh travel distance
+    pub fn site(&self) -> Option<Entity> {
+        self.tasks.iter().find_map(|t| match t {
+            Task::MoveTo(e) => Some(*e),
+            _ => None,
+        })
+    }
+}
+
+#[derive(Resource, Default, Debug)]
+pub struct JobBoard {
+    jobs: HashMap<JobId, Job>,
+    next_id: u64,
+}
+
+impl JobBoard {
+    pub fn post(&mut self, job: Job) -> JobId {
+        let id = JobId(self.next_id);
+        self.next_id += 1;
+        self.jobs.insert(id, job);
+        id
+    }
+
+    pub fn get(&self, id: JobId) -> Option<&Job> {
+        self.jobs.get(&id)
+    }
+
+    pub fn remove(&mut self, id: JobId) -> Option<Job> {
+        self.jobs.remove(&id)
+    }
+
+    // Puts a claimed job back up for grabs
+    pub fn release(&mut self, id: JobId) {
+        if let Some(job) = self.jobs.get_mut(&id) {
+            job.claimed_by = None;
+        }
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (JobId, &Job)> {
+        self.jobs.iter().map(|(id, job)| (*id, job))
+    }
+
+    pub fn count_posted_by(&self, poster: Entity, category: JobCategory) -> usize {
+        self.jobs.values().filter(|j| j.poster == poster && j.category == category).count()
+    }
+}
+
+// Player weights per job category; 0 disables a category entirely
+#[derive(Resource, Debug, Clone)]
+pub struct JobPriorities(pub HashMap<JobCategory, i32>);
+
+impl Default for JobPriorities {
+    fn default() -> Self {
+        Self(JobCategory::ALL.iter().map(|c| (*c, 3)).collect())
+    }
+}
+
+impl JobPriorities {
+    pub const MAX: i32 = 5;
+
+    pub fn weight(&self, category: JobCategory) -> i32 {
+        self.0.get(&category).copied().unwrap_or(3)
+    }
+}
+
+#[derive(Component, Debug, Clone)]
+pub struct CurrentJob {
+    pub id: JobId,
+    pub step: usize,
+    // Goods in hand, keeping the age of each batch
+    pub carrying: Option<(Good, Vec<Batch>)>,
+    // Ticks spent on the current `Task::Work`
+    pub progress: u32,
+}
+
+// Agent -> entity it is currently working at
+#[derive(Component)]
+#[relationship(relationship_target = Workers)]
+pub struct WorkingAt(pub Entity);
+
+#[derive(Component)]
+#[relationship_target(relationship = WorkingAt)]
+pub struct Workers(Vec<Entity>);
+
+impl Workers {
+    pub fn count(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = Entity> + '_ {
+        self.0.iter().copied()
+    }
+}
+
+#[derive(Event, Debug, Clone, Copy)]
+pub struct JobCompleted {
+    pub id: JobId,
+    pub worker: Entity,
+    pub category: JobCategory,
+    pub poster: Entity,
+}
+
+#[derive(Event, Debug, Clone, Copy)]
+pub struct JobFailed {
+    pub id: JobId,
+    pub worker: Entity,
+    pub reason: &'static str,
+}
+
+#[derive(Component)]
+struct JobPrioritiesText;
+
+// Unclaimed jobs whose poster is gone (demolished, bankrupt) can never be worked
+fn drop_orphaned_jobs(
+    mut board: ResMut<JobBoard>,
+    entities: Query<()>,
+) {
+    board.jobs.retain(|_, job| job.claimed_by.is_some() || entities.contains(job.poster));
+}
+
+fn post_construction_jobs(
+    mut board: ResMut<JobBoard>,
+    sites: Query<(Entity, &ConstructionSite), Added<ConstructionSite>>,
+) {
+    for (site, construction) in &sites {
+        board.post(Job::new(JobCategory::Build, 3, site, vec![
+            Task::MoveTo(site),
+            Task::Work { target: site, ticks: construction.work_ticks },
+        ]).with_skill(Skill::Construction, 0.0));
+    }
+}
+
+fn post_operate_jobs(
+    config: Res<JobConfig>,
+    mut board: ResMut<JobBoard>,
+    workstations: Query<(Entity, &Workstation, &Owner, &ChildOf), With<Transform>>,
+    operational: Query<(), With<Operational>>,
+) {
+    for (workstation, ws, business, building) in &workstations {
+        // Nobody is hired onto a building site or a building with nowhere to deliver to
+        if !operational.contains(building.parent()) { continue; }
+        // Shifts stay on the board until completed, so this counts both open and worked slots
+        let posted = board.count_posted_by(workstation, JobCategory::Operate);
+        for _ in posted..ws.worker_slots as usize {
+            board.post(Job::new(JobCategory::Operate, 2, workstation, vec![
+                Task::MoveTo(workstation),
+                Task::Work { target: workstation, ticks: config.shift_ticks },
+            ]).with_employer(business.0).with_skill(Skill::for_good(ws.output), 0.0));
+        }
+    }
+}
+
+fn release_jobs_of_departed(
+    mut board: ResMut<JobBoard>,
+    mut died: EventReader<CharacterDied>,
+    mut emigrated: EventReader<CharacterEmigrated>,
+    jobs: Query<&CurrentJob>,
+) {
+    let departed = died.read().map(|e| e.entity).chain(emigrated.read().map(|e| e.entity));
+    for agent in departed {
+        if let Ok(job) = jobs.get(agent) {
+            board.release(job.id);
+        }
+    }
+}
+
+fn claim_jobs(
+    mut commands: Commands,
+    config: Res<JobConfig>,
+    mut board: ResMut<JobBoard>,
+    priorities: Res<JobPriorities>,
+    skill_config: Res<SkillConfig>,
+    idle: Query<(Entity, &Activity, &Transform, Option<&EmployedBy>, Has<Hauler>, Option<&Skills>), (With<Health>, Without<CurrentJob>)>,
+    // Job sites may be children of a building, so their world position comes from GlobalTransform
+    positions: Query<&GlobalTransform>,
+) {
+    for (agent, activity, transform, employer, is_hauler, skills) in &idle {
+        if *activity != Activity::Working { continue; }
+        let pos = transform.translation.truncate();
+        let level = |skill: Skill| skills.map_or(0.0, |s| s.level(skill));
+
+        let best = board
+            .iter()
+            .filter(|(_, job)| job.claimed_by.is_none())
+            .filter(|(_, job)| priorities.weight(job.category) > 0)
+            .filter(|(_, job)| job.employer.is_none_or(|e| employer.is_some_and(|emp| emp.0 == e)))
+            .filter(|(_, job)| job.category != JobCategory::Haul || is_hauler)
+            .filter(|(_, job)| job.required_skill.is_none_or(|(skill, min)| level(skill) >= min))
+            .map(|(id, job)| {
+                let distance = job.site()
+                    .and_then(|site| positions.get(site).ok())
+                    .map_or(0.0, |t| t.translation().truncate().distance(pos));
+                // Agents lean towards work they are good at
+                let skill_bonus = job.required_skill.map_or(0.0, |(skill, _)| level(skill) * skill_config.assignment_weight);
+                let score = (job.priority * priorities.weight(job.category)) as f32 + skill_bonus
+                    - distance / config.distance_per_priority;
+                (id, score)
+            })
+            .max_by(|a, b| a.1.total_cmp(&b.1));
+
+        if let Some((id, _)) = best {
+            if let Some(job) = board.jobs.get_mut(&id) {
+                job.claimed_by = Some(agent);
+            }
+            commands.entity(agent).insert(CurrentJob { id, step: 0, carrying: None, progress: 0 });
+        }
+    }
+}
+
+fn progress_work(mut agents: Query<&mut CurrentJob, With<WorkingAt>>) {
+    for mut job in &mut agents {
+        job.progress += 1;
+    }
+}
+
+fn execute_jobs(
+    mut commands: Commands,
+    config: Res<JobConfig>,
+    mut board: ResMut<JobBoard>,
+    mut agents: Query<(Entity, &mut CurrentJob, &Activity, &Transform, &mut Destination, Option<&Hauler>, Has<WorkingAt>)>,
+    positions: Query<&GlobalTransform, Without<CurrentJob>>,
+    drop_offs: Query<&DropOff>,
+    mut storages: Query<&mut Storage>,
+    spots: Query<(&WorkerSpots, &GlobalTransform, Option<&Workers>)>,
+    storage_config: Res<StorageConfig>,
+    capacities: Query<&StorageCapacity>,
+    stockpiles: Query<(Entity, &Owner, &GlobalTransform), With<GroundStockpile>>,
+    mut completed: EventWriter<JobCompleted>,
+    mut failed: EventWriter<JobFailed>,
+) {
+    for (agent, mut current, activity, transform, mut destination, hauler, working) in &mut agents {
+        let Some(job) = board.get(current.id).cloned() else {
+            commands.entity(agent).remove::<(CurrentJob, WorkingAt)>();
+            failed.write(JobFailed { id: current.id, worker: agent, reason: "job was cancelled" });
+            continue;
+        };
+
+        // Off shift: hand the job back for someone else
+        if *activity != Activity::Working {
+            board.release(current.id);
+            commands.entity(agent).remove::<(CurrentJob, WorkingAt)>();
+            continue;
+        }
+
+        let Some(task) = job.tasks.get(current.step).copied() else {
+            board.remove(current.id);
+            commands.entity(agent).remove::<(CurrentJob, WorkingAt)>();
+            completed.write(JobCompleted { id: current.id, worker: agent, category: job.category, poster: job.poster });
+            continue;
+        };
+
+        let pos = transform.translation.truncate();
+        let id = current.id;
+        let mut fail = |reason: &'static str, commands: &mut Commands, board: &mut JobBoard| {
+            board.remove(id);
+            commands.entity(agent).remove::<(CurrentJob, WorkingAt)>();
+            failed.write(JobFailed { id, worker: agent, reason });
+        };
+
+        match task {
+            Task::MoveTo(target) => {
+                let Ok(target_transform) = positions.get(target) else {
+                    fail("target is gone", &mut commands, &mut board);
+                    continue;
+                };
+                let target_pos = target_transform.translation().truncate();
+                destination.0 = target_pos;
+                if pos.distance(target_pos) <= config.reach {
+                    current.step += 1;
+                }
+            }
+            Task::PickUp { from, good, quantity } => {
+                let carry_weight = hauler.map_or(config.base_carry_weight, |h| h.carry_weight);
+                let limit = quantity.min((carry_weight / good.weight()).floor() as i32);
+                let taken = storages.get_mut(from).map_or(Vec::new(), |mut storage| storage.take_batches(good, limit));
+                if Batch::total(&taken) == 0 {
+                    fail("nothing to pick up", &mut commands, &mut board);
+                    continue;
+                }
+                current.carrying = Some((good, taken));
+                current.step += 1;
+            }
+            Task::DropOff { to } => {
+                let storage_entity = drop_offs.get(to).map_or(to, |d| d.storage);
+                if let Some((good, batches)) = current.carrying.take() {
+                    let room = match (capacities.get(storage_entity), storages.get(storage_entity)) {
+                        (Ok(capacity), Ok(storage)) => capacity.room_for(&storage, good),
+                        _ => i32::MAX,
+                    };
+                    let (kept, rest) = Batch::split(batches, room);
+                    if let Ok(mut storage) = storages.get_mut(storage_entity) {
+                        storage.add_batches(good, kept);
+                    }
+                    // A full warehouse gets the rest piled up outside
+                    if !rest.is_empty() {
+                        let at = positions.get(to).map_or(pos, |t| t.translation().truncate());
+                        spill_to_stockpile(&mut commands, &storage_config, &stockpiles, &mut storages, storage_entity, at, good, rest);
+                    }
+                }
+                current.step += 1;
+            }
+            Task::Work { target, ticks } => {
+                if !working {
+                    current.progress = 0;
+                    commands.entity(agent).insert(WorkingAt(target));
+                    // Step onto the next free operator spot, if the target has them
+                    if let Ok((spots, target_transform, workers)) = spots.get(target) {
+                        let taken = workers.map_or(0, |w| w.count());
+                        if let Some(spot) = spots.0.get(taken % spots.0.len().max(1)) {
+                            destination.0 = target_transform.transform_point(spot.extend(0.0)).truncate();
+                        }
+                    }
+                } else if current.progress >= ticks {
+                    commands.entity(agent).remove::<WorkingAt>();
+                    current.step += 1;
+                }
+            }
+        }
+    }
+}
+
+fn finish_construction(
+    mut commands: Commands,
+    mut completed: EventReader<JobCompleted>,
+    sites: Query<(), With<ConstructionSite>>,
+) {
+    for ev in completed.read() {
+        if ev.category == JobCategory::Build && sites.contains(ev.poster) {
+            commands.entity(ev.poster).remove::<ConstructionSite>();
+        }
+    }
+}
+
+// The priority actions (F1-F4 by default) cycle the player priority of each job category
+fn adjust_job_priorities(
+    actions: Res<Actions>,
+    mut priorities: ResMut<JobPriorities>,
+) {
+    let bindings = [Action::PriorityBuild, Action::PriorityHaul, Action::PriorityOperate, Action::PriorityHarvest];
+    for (action, category) in bindings.iter().zip(JobCategory::ALL) {
+        if actions.just_pressed(*action) {
+            let weight = priorities.0.entry(category).or_insert(3);
+            *weight = (*weight + 1) % (JobPriorities::MAX + 1);
+        }
+    }
+}
+
+fn show_job_priorities(
+    mut commands: Commands,
+    priorities: Res<JobPriorities>,
+    mut text_query: Query<&mut Text, With<JobPrioritiesText>>,
+) {
+    if !priorities.is_changed() { return; }
+    let line = JobCategory::ALL
+        .iter()
+        .enumerate()
+        .map(|(i, c)| format!("F{} {}: {}", i + 1, c.name(), priorities.weight(*c)))
+        .collect::<Vec<_>>()
+        .join("   ");
+
+    if let Ok(mut text) = text_query.single_mut() {
+        text.0 = line;
+    } else {
+        commands.spawn((
+            Node {
+                position_type: PositionType::Absolute,
+                bottom: Val::Px(10.0),
+                left: Val::Px(10.0),
+                ..Default::default()
+            },
+            Text::new(line),
+            TextFont { font_size: 14.0, ..default() },
+            TextColor(Color::WHITE),
+            JobPrioritiesText,
+        ));
+    }
+}
@@ -0,0 +1,141 @@
+pub struct LogisticsSystems;
+use crate::*;
+use crate::jobs::{CurrentJob, Job, JobBoard, JobCategory, Task};
+use crate::production::{Good, Storage, Workstation};
+use crate::skills::Skill;
+use crate::ownership::Owner;
+use crate::storage::{GroundStockpile, StorageCapacity};
+use bevy::prelude::*;
+use bevy::ecs::schedule::common_conditions::on_event;
+use std::collections::HashMap;
+
+impl Plugin for LogisticsSystems {
+    fn build(&self, app: &mut App) {
+        app
+            .insert_resource(LogisticsConfig::default())
+            .add_systems(Update, recruit_haulers)
+            .add_systems(Update, post_haul_jobs.run_if(on_event::<WorldTick>));
+    }
+}
+
+#[derive(Resource, Debug, Clone, Copy)]
+pub struct LogisticsConfig {
+    // Chance a newly arrived character takes up hauling
+    pub hauler_share: f64,
+    pub carry_weight: f32,
+}
+
+impl Default for LogisticsConfig {
+    fn default() -> Self {
+        Self {
+            hauler_share: 0.3,
+            carry_weight: 10.0,
+        }
+    }
+}
+
+impl Good {
+    // Carry weight of a single unit
+    pub(crate) fn weight(&self) -> f32 {
+        match self {
+            Good::Food => 1.0,
+            Good::Grain => 1.0,
+            Good::Wood => 3.0,
+        }
+    }
+}
+
+// A place goods can be delivered to. Deliveries land in the `Storage` of `storage`,
+// which may be the drop-off itself or e.g. the business owning a warehouse.
+#[derive(Component, Debug, Clone, Copy)]
+pub struct DropOff {
+    pub storage: Entity,
+}
+
+// A character able to take haul jobs
+#[derive(Component, Debug, Clone, Copy)]
+pub struct Hauler {
+    pub carry_weight: f32,
+}
+
+fn recruit_haulers(
+    mut commands: Commands,
+    config: Res<LogisticsConfig>,
+    new_characters: Query<Entity, Added<Health>>,
+) {
+    let mut rng = rand::rng();
+    for character in &new_characters {
+        if rng.random_bool(config.hauler_share) {
+            commands.entity(character).insert(Hauler { carry_weight: config.carry_weight });
+        }
+    }
+}
+
+// Posts haul jobs for output waiting in workstation buffers and on ground stockpiles, one load per job, to the
+// business' closest drop-off. Fuller buffers get a higher priority; the job board weighs in distance.
+fn post_haul_jobs(
+    config: Res<LogisticsConfig>,
+    mut board: ResMut<JobBoard>,
+    buffers: Query<(Entity, &Storage, &GlobalTransform, &Owner), Or<(With<Workstation>, With<GroundStockpile>)>>,
+    drop_offs: Query<(Entity, &DropOff, &GlobalTransform)>,
+    warehouses: Query<(&Storage, &StorageCapacity)>,
+    agents: Query<&CurrentJob>,
+) {
+    // Goods already promised to a posted job stay put until picked up, and the room they will take is spoken for
+    let mut reserved: HashMap<(Entity, Good), i32> = HashMap::new();
+    let mut incoming: HashMap<(Entity, Good), i32> = HashMap::new();
+    for (_, job) in board.iter() {
+        // Tasks before this step are done; goods already picked up no longer sit at their source
+        let step = job.claimed_by.and_then(|agent| agents.get(agent).ok()).map_or(0, |current| current.step);
+        let mut load = None;
+        for (i, task) in job.tasks.iter().enumerate() {
+            match task {
+                Task::PickUp { from, good, quantity } => {
+                    if i >= step {
+                        *reserved.entry((*from, *good)).or_default() += quantity;
+                    }
+                    load = Some((*good, *quantity));
+                }
+                Task::DropOff { to } => {
+                    if let (Some((good, quantity)), Ok((_, drop_off, _))) = (load, drop_offs.get(*to)) {
+                        *incoming.entry((drop_off.storage, good)).or_default() += quantity;
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    for (source, storage, transform, business) in &buffers {
+        let pos = transform.translation().truncate();
+        let Some((target, _, target_storage)) = drop_offs
+            .iter()
+            .filter(|(_, drop_off, _)| drop_off.storage == business.0)
+            .map(|(e, d, t)| (e, t.translation().truncate().distance(pos), d.storage))
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+        else { continue; };
+
+        for (good, amount) in &storage.goods {
+            let room = warehouses
+                .get(target_storage)
+                .map_or(i32::MAX, |(stock, capacity)| capacity.room_for(stock, *good))
+                .saturating_sub(incoming.get(&(target_storage, *good)).copied().unwrap_or(0));
+            // Overflow waits on the ground until its warehouse has room again
+            if room <= 0 { continue; }
+            let mut available = (amount - reserved.get(&(source, *good)).copied().unwrap_or(0)).min(room);
+            *incoming.entry((target_storage, *good)).or_default() += available.max(0);
+            let load = ((config.carry_weight / good.weight()).floor() as i32).max(1);
+            let priority = (available / load).clamp(1, 5);
+            while available > 0 {
+                let quantity = available.min(load);
+                available -= quantity;
+                board.post(Job::new(JobCategory::Haul, priority, source, vec![
+                    Task::MoveTo(source),
+                    Task::PickUp { from: source, good: *good, quantity },
+                    Task::MoveTo(target),
+                    Task::DropOff { to: target },
+                ]).with_skill(Skill::Hauling, 0.0));
+            }
+        }
+    }
+}
@@ -1,14 +1,37 @@
 mod components;
+mod input;
+mod camera;
 mod materials;
 mod world_grid;
 mod states;
 mod building;
 mod production;
+mod needs;
+mod population;
+mod clock;
+mod market;
+mod finance;
+mod ai_business;
+mod logistics;
+mod jobs;
+mod skills;
+mod inspector;
+mod metrics;
+mod charts;
+mod ownership;
+mod storage;
+mod spoilage;
+mod farming;
+mod zoning;
+mod minimap;
+mod heatmap;
+mod notifications;
 
 use std::time::Duration;
-use crate::{building::BuildingControlState, production::ProductionSystems};
+use crate::{ai_business::AiBusinessSystems, building::BuildingControlState, camera::CameraSystems, charts::ChartsSystems, clock::ClockSystems, farming::FarmingSystems, finance::FinanceSystems, heatmap::HeatmapSystems, input::{Action, Actions, InputSystems}, inspector::InspectorSystems, jobs::{CurrentJob, JobSystems}, logistics::LogisticsSystems, market::MarketSystems, metrics::MetricsSystems, minimap::MinimapSystems, needs::NeedsSystems, notifications::{Notification, NotificationSystems}, ownership::OwnershipSystems, population::PopulationSystems, production::ProductionSystems, skills::SkillSystems, spoilage::SpoilageSystems, storage::StorageSystems, zoning::ZoningSystems};
 
-use bevy::{input::mouse::{MouseMotion, MouseWheel}, math::ops::powf, platform::collections::HashSet, prelude::{Name, *}, render::view::RenderLayers};
+use bevy::{platform::collections::HashSet, prelude::{Name, *}, render::view::RenderLayers};
+use bevy::ecs::schedule::common_conditions::on_event;
 use bevy_lunex::{*, prelude::*};
 use components::{*, Velocity};
 use materials::{CommonMaterials, setup_common_materials};
@@ -17,7 +40,6 @@ use rand::Rng;
 use bevy_rapier2d::prelude::*;
 use bevy_rapier2d::render::RapierDebugRenderPlugin;
 use bevy_spatial::{kdtree::KDTree2, AutomaticUpdate, SpatialAccess, SpatialStructure, TransformMode};
-use bevy::ecs::schedule::common_conditions::on_event; // added
 
 use crate::states::GameControlState;
 
@@ -57,25 +79,97 @@ struct WorldTimer(Timer);
 #[derive(Resource)]
 struct LongBehaviourTimer(Timer);
 
+// Command line options for how the simulation runs
+#[derive(Resource, Debug, Clone)]
+struct RunOptions {
+    // No window or GPU, ticks run as fast as `speed` allows
+    headless: bool,
+    // Exit after this many world ticks
+    max_ticks: Option<u64>,
+    // Simulation speed multiplier for headless runs
+    speed: f32,
+    metrics_out: std::path::PathBuf,
+    // Export metrics when the app closes; always on for headless runs
+    export_on_exit: bool,
+}
+
+impl RunOptions {
+    fn from_args() -> Self {
+        let args: Vec<String> = std::env::args().collect();
+        let value = |flag: &str| args.iter().position(|a| a == flag).and_then(|i| args.get(i + 1)).cloned();
+        Self {
+            headless: args.iter().any(|a| a == "--headless"),
+            max_ticks: value("--ticks").and_then(|v| v.parse().ok()),
+            speed: value("--speed").and_then(|v| v.parse().ok()).unwrap_or(50.0),
+            metrics_out: value("--metrics-out").unwrap_or_else(|| "metrics".to_string()).into(),
+            export_on_exit: value("--metrics-out").is_some(),
+        }
+    }
+}
+
 fn main() {
     let dbg_enabled = std::env::args().any(|a| a == "--dbg" || a == "--debug" || a == "-d")
         || std::env::var("DBG").map(|v| v == "1" || v.eq_ignore_ascii_case("true")).unwrap_or(false)
         || std::env::var("BEVY_DEBUG").map(|v| v == "1" || v.eq_ignore_ascii_case("true")).unwrap_or(false);
 
+    let run_options = RunOptions::from_args();
+
     let mut app = App::new();
+    if run_options.headless {
+        app.add_plugins((
+            DefaultPlugins
+                .set(WindowPlugin { primary_window: None, exit_condition: bevy::window::ExitCondition::DontExit, ..default() })
+                .set(bevy::render::RenderPlugin {
+                    render_creation: bevy::render::settings::WgpuSettings { backends: None, ..default() }.into(),
+                    ..default()
+                })
+                .set(bevy::app::ScheduleRunnerPlugin::run_loop(Duration::ZERO))
+                .disable::<bevy::winit::WinitPlugin>(),
+            UiLunexPlugins,
+        ));
+        app.add_systems(Startup, speed_up_headless_time);
+    } else {
+        app.add_plugins((DefaultPlugins, UiLunexPlugins));
+    }
+    if run_options.max_ticks.is_some() {
+        app.add_systems(Update, exit_after_ticks.run_if(on_event::<WorldTick>));
+    }
+
     app
+        .insert_resource(run_options)
         .add_plugins(RapierPhysicsPlugin::<NoUserData>::pixels_per_meter(100.0))
         .insert_resource(WorldTimer(Timer::from_seconds(1.0, TimerMode::Repeating)))
         .insert_resource(LongBehaviourTimer(Timer::from_seconds(5.0, TimerMode::Repeating)))
         .insert_resource(WorldGrid::new(160, 160, 25))
         .insert_resource(DebugOptions { enabled: dbg_enabled })
-        .add_plugins((DefaultPlugins, UiLunexPlugins))
         .add_plugins(Visual)
         .add_plugins(Movement)
+        .add_plugins(InputSystems)
+        .add_plugins(NotificationSystems)
         .add_plugins(CameraControls)
+        .add_plugins(CameraSystems)
         .add_plugins((GameDefaultPlugins, GameBuildingPlugins))
+        .add_plugins(OwnershipSystems)
         .add_plugins(ProductionSystems)
-        //.add_plugins(HumanPlugins)
+        .add_plugins(StorageSystems)
+        .add_plugins(SpoilageSystems)
+        .add_plugins(NeedsSystems)
+        .add_plugins(PopulationSystems)
+        .add_plugins(ClockSystems)
+        .add_plugins(MarketSystems)
+        .add_plugins(FinanceSystems)
+        .add_plugins(AiBusinessSystems)
+        .add_plugins(LogisticsSystems)
+        .add_plugins(JobSystems)
+        .add_plugins(FarmingSystems)
+        .add_plugins(ZoningSystems)
+        .add_plugins(SkillSystems)
+        .add_plugins(InspectorSystems)
+        .add_plugins(MetricsSystems)
+        .add_plugins(ChartsSystems)
+        .add_plugins(MinimapSystems)
+        .add_plugins(HeatmapSystems)
+        .add_plugins(HumanPlugins)
         .insert_state(GameControlState::Default);
 
     if dbg_enabled {
@@ -93,15 +187,7 @@ impl Plugin for Movement {
         app
             .add_event::<WorldTick>()
             .add_systems(Startup, (setup_common_materials).chain())
-            .add_systems(Update, world_tick_emitter)
-            .add_systems(
-                Update,
-                (
-                    update_hunger.run_if(on_event::<WorldTick>),
-                    update_thirst.run_if(on_event::<WorldTick>),
-                    update_sleep.run_if(on_event::<WorldTick>),
-                ),
-            );
+            .add_systems(Update, world_tick_emitter);
     }
 }
 
@@ -114,7 +200,6 @@ impl Plugin for CameraControls {
                 (
                     track_mouse_world_position,
                     cursor_event_to_state, 
-                    camera_controls,
                 ),
             );
     }
@@ -132,8 +217,8 @@ impl Plugin for HumanPlugins {
             .with_frequency(Duration::from_secs_f32(1.))
             .with_transform(TransformMode::GlobalTransform)
             .with_spatial_ds(SpatialStructure::KDTree2))
-        .add_systems(Startup, (add_animal, add_food))
-        .add_systems(Update, (handle_food_collisions, food_search))
+        .add_systems(PostStartup, add_food)
+        .add_systems(Update, food_search)
         .add_systems(FixedUpdate, update_movement);
     }
 }
@@ -147,9 +232,9 @@ impl Plugin for GameDefaultPlugins {
 
 fn game_state_control_default(
     mut next_state: ResMut<NextState<GameControlState>>,
-    keys: Res<ButtonInput<KeyCode>>,
+    actions: Res<Actions>,
 ) {
-    if keys.just_pressed(KeyCode::KeyB) {
+    if actions.just_pressed(Action::ToggleBuildMode) {
         next_state.set(GameControlState::Building)
     }
 }
@@ -168,7 +253,7 @@ fn visual_setup(mut commands: Commands) {
 
 fn food_search(
     tree: Res<KDTree2<FoodTracking>>,
-    query: Query<(Entity, &Transform), (With<Speed>, With<Destination>)>,
+    query: Query<(Entity, &Transform), (With<Speed>, With<Destination>, Without<CurrentJob>)>,
     mut destination_query: Query<&mut Destination>,
     mut gizmos: Gizmos
 ) {
@@ -215,21 +300,26 @@ fn world_tick_emitter(
     }
 }
 
-fn update_hunger(mut query: Query<&mut Hunger>) {
-    for mut hunger in &mut query {
-        hunger.value = update_parameter(&hunger.value, |x| (hunger.decay)(x));
-    }
-}
-
-fn update_thirst(mut query: Query<&mut Thirst>) {
-    for mut thirst in &mut query {
-        thirst.value = update_parameter(&thirst.value, |x| (thirst.decay)(x));
-    }
+fn speed_up_headless_time(
+    options: Res<RunOptions>,
+    mut time: ResMut<Time<Virtual>>,
+    mut notifications: EventWriter<Notification>,
+) {
+    notifications.write(Notification::info("Run", format!("Headless run, {}x speed", options.speed)));
+    // Virtual time keeps its default max delta, below the one second tick, so a frame never spans two ticks
+    time.set_relative_speed(options.speed);
 }
 
-fn update_sleep(mut query: Query<&mut Sleep>) {
-    for mut sleep in &mut query {
-        sleep.value = update_parameter(&sleep.value, |x| (sleep.decay)(x));
+fn exit_after_ticks(
+    options: Res<RunOptions>,
+    mut ticks: Local<u64>,
+    mut exit: EventWriter<AppExit>,
+    mut notifications: EventWriter<Notification>,
+) {
+    *ticks += 1;
+    if options.max_ticks.is_some_and(|max| *ticks >= max) {
+        notifications.write(Notification::info("Run", format!("Reached {} ticks, exiting", *ticks)));
+        exit.write(AppExit::Success);
     }
 }
 
@@ -274,51 +364,6 @@ fn update_destination(time: Res<Time>, mut timer: ResMut<LongBehaviourTimer>, mu
     }
 }
 
-fn update_parameter<F>(value: &f32, f: F) -> f32
-where
-    F: Fn(&f32) -> f32,
-{
-    f(value)
-}
-
-fn add_animal(
-    mut commands: Commands,
-    mut meshes: ResMut<Assets<Mesh>>,
-    common_materials: Res<CommonMaterials>,
-) {
-    let names = ["Vlad"];
-    let mut rng = rand::rng();
-    let mut bundles = Vec::with_capacity(names.len());
-    let mesh_handle = meshes.add(Mesh::from(Circle::new(5.0)));
-    for n in names {
-        let x = rng.random_range(-400.0..=400.0);
-        let y = rng.random_range(-400.0..=400.0);
-        let character = CharacterBundle {
-            name: EntityLabel(n.to_string()),
-            health: Health(100.0),
-            hunger: Hunger { value: 100.0, decay: |x| x - 1.0 },
-            thirst: Thirst { value: 100.0, decay: |x| x - 1.0 },
-            sleep: Sleep { value: 100.0, decay: |x| x - 1.0 },
-            speed: Speed(50.0),
-            velocity: Velocity(Vec2::ZERO),
-            destination: Destination(Vec2 { x: 0.0, y: 0.0 }),
-            tracked: TrackedByKDTree,
-        };
-
-        let material_handle = common_materials.hero.clone();
-    
-        let visuals = VisualBundle {
-            mesh: Mesh2d(mesh_handle.clone()),
-            material: MeshMaterial2d(material_handle),
-            transform: Transform::from_xyz(x, y, 0.0),
-        };
-        let collision = CollisionBundle::circle_sensor(
-            5.0, RigidBody::KinematicPositionBased, true);
-        bundles.push((character, visuals, collision));
-    }
-    commands.spawn_batch(bundles);
-}
-
 fn add_food(
     mut commands: Commands,
     mut meshes: ResMut<Assets<Mesh>>,
@@ -348,56 +393,6 @@ fn add_food(
     commands.spawn_batch(bundles);
 }
 
-fn handle_food_collisions(
-    mut commands: Commands,
-    mut collision_events: EventReader<CollisionEvent>,
-    food_query: Query<(), With<Food>>,
-) {
-    for collision_event in collision_events.read() {
-        if let CollisionEvent::Started(e1, e2, _flags) = collision_event {
-            for entity in [e1, e2] {
-                if food_query.get(*entity).is_ok() {
-                    commands.entity(*entity).despawn();
-                }
-            }
-        }
-    }
-}
-fn camera_controls(
-    mut mouse_motion_events: EventReader<MouseMotion>,
-    mut scroll_events: EventReader<MouseWheel>,
-    buttons: Res<ButtonInput<MouseButton>>,
-    mut query: Query<(&mut Transform, &mut Projection), With<Camera2d>>,
-    time: Res<Time>,
-) {
-    let Ok((mut transform, mut projection)) = query.single_mut() else { return; };
-
-    if buttons.pressed(MouseButton::Left) {
-        let mut drag_delta = Vec2::ZERO;
-        for ev in mouse_motion_events.read() { drag_delta += ev.delta; }
-        if drag_delta != Vec2::ZERO {
-            transform.translation.x -= drag_delta.x;
-            transform.translation.y += drag_delta.y;
-        }
-    } else {
-        for _ in mouse_motion_events.read() {}
-    }
-
-    if let Projection::Orthographic(ortho) = &mut *projection {
-        let dt = time.delta_secs();
-        for ev in scroll_events.read() {
-            if ev.y > 0.0 { // zoom in
-                ortho.scale *= powf(0.0625, dt);
-            } else if ev.y < 0.0 { // zoom out
-                ortho.scale *= powf(16., dt);
-            }
-        }
-        ortho.scale = ortho.scale.clamp(0.05, 50.0);
-    } else {
-        for _ in scroll_events.read() {}
-    }
-}
-
 fn draw_grid_enum(grid: Res<WorldGrid>, mut commands: Commands){
     let tile_size = grid.scale() as f32;
     let tiles_wide = grid.width() as f32;
@@ -462,8 +457,8 @@ fn track_mouse_world_position(
     grid: Res<WorldGrid>,
     mut ev_writer: EventWriter<CursorWorldEvent>,
 ) {
-    let (camera, camera_transform) = camera_q.single().unwrap();
-    let window = windows.single().unwrap();
+    // Headless runs have no window to track
+    let (Ok((camera, camera_transform)), Ok(window)) = (camera_q.single(), windows.single()) else { return; };
 
     if let Some(screen_pos) = window.cursor_position() {
         if let Ok(world_pos) = camera.viewport_to_world_2d(camera_transform, screen_pos) {
@@ -0,0 +1,371 @@
+pub struct MarketSystems;
+use crate::*;
+use crate::ownership::{Factions, Owner};
+use crate::population::House;
+use crate::production::{Bussiness, Good, Storage, Workstation};
+use crate::storage::StorageCapacity;
+use bevy::prelude::*;
+use bevy::ecs::schedule::common_conditions::on_event;
+use std::collections::{HashMap, VecDeque};
+
+impl Plugin for MarketSystems {
+    fn build(&self, app: &mut App) {
+        let config = MarketConfig::default();
+        app
+            .insert_resource(Market::new(&config))
+            .insert_resource(config)
+            .add_event::<TradeExecuted>()
+            .add_systems(Startup, spawn_town)
+            .add_systems(
+                Update,
+                (derive_input_targets, apply_trade_policies, post_town_demand, clear_market)
+                    .chain()
+                    .run_if(on_event::<WorldTick>),
+            );
+    }
+}
+
+#[derive(Resource, Debug, Clone)]
+pub struct MarketConfig {
+    pub base_prices: HashMap<Good, f32>,
+    // Fraction of the price moved per tick at full supply/demand imbalance
+    pub price_adjust_rate: f32,
+    pub min_price: f32,
+    pub history_len: usize,
+    // Money the town earns per resident each tick to fund its purchases
+    pub town_income_per_resident: i32,
+    pub town_food_per_resident: i32,
+    // Items' worth of inputs businesses keep in stock for each workstation
+    pub input_stock_items: i32,
+}
+
+impl Default for MarketConfig {
+    fn default() -> Self {
+        Self {
+            base_prices: HashMap::from([(Good::Food, 10.0), (Good::Grain, 4.0), (Good::Wood, 6.0)]),
+            price_adjust_rate: 0.05,
+            min_price: 0.1,
+            history_len: 1024,
+            town_income_per_resident: 5,
+            town_food_per_resident: 1,
+            input_stock_items: 5,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OrderSide {
+    Buy,
+    Sell,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Order {
+    pub good: Good,
+    pub side: OrderSide,
+    pub quantity: i32,
+    // Sells only at or above, buys only at or below this price
+    pub limit_price: f32,
+}
+
+// Standing orders of a `Storage` owner, matched against every tick
+#[derive(Component, Default, Debug, Clone)]
+pub struct MarketOrders(pub Vec<Order>);
+
+// Keeps `MarketOrders` in line with simple stock targets
+#[derive(Component, Default, Debug, Clone)]
+pub struct TradePolicy {
+    // Sell everything above this stock
+    pub sell_above: HashMap<Good, i32>,
+    // Buy up to this stock
+    pub buy_below: HashMap<Good, i32>,
+    // Stock of workstation inputs to keep, derived from the owner's workstations every tick
+    pub input_targets: HashMap<Good, i32>,
+    // Limit price relative to the market price, e.g. 0.9 sells at no less than 90%
+    pub sell_margin: f32,
+    pub buy_margin: f32,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct PricePoint {
+    pub tick: u64,
+    pub price: f32,
+    pub supply: i32,
+    pub demand: i32,
+    pub volume: i32,
+}
+
+#[derive(Debug, Clone)]
+pub struct GoodMarket {
+    pub price: f32,
+    pub history: VecDeque<PricePoint>,
+}
+
+#[derive(Resource, Debug, Clone)]
+pub struct Market {
+    goods: HashMap<Good, GoodMarket>,
+    tick: u64,
+}
+
+impl Market {
+    pub fn new(config: &MarketConfig) -> Self {
+        let goods = Good::ALL
+            .iter()
+            .map(|good| {
+                let price = config.base_prices.get(good).copied().unwrap_or(1.0);
+                (*good, GoodMarket { price, history: VecDeque::new() })
+            })
+            .collect();
+        Self { goods, tick: 0 }
+    }
+
+    pub fn price(&self, good: Good) -> f32 {
+        self.goods.get(&good).map_or(0.0, |m| m.price)
+    }
+
+    pub fn history(&self, good: Good) -> impl Iterator<Item = &PricePoint> {
+        self.goods.get(&good).into_iter().flat_map(|m| m.history.iter())
+    }
+
+    pub fn last(&self, good: Good) -> Option<&PricePoint> {
+        self.goods.get(&good).and_then(|m| m.history.back())
+    }
+}
+
+#[derive(Event, Debug, Clone, Copy)]
+pub struct TradeExecuted {
+    pub good: Good,
+    pub seller: Entity,
+    pub buyer: Entity,
+    pub quantity: i32,
+    pub price: f32,
+}
+
+// Stands in for household consumers until residents trade on their own
+#[derive(Component)]
+pub struct Town;
+
+fn spawn_town(mut commands: Commands, factions: Res<Factions>) {
+    commands.spawn((EntityLabel("Town".to_string()), Town, Owner(factions.world), Storage::with_money(500), MarketOrders::default()));
+}
+
+// Businesses keep a few items' worth of what their workstations consume
+fn derive_input_targets(
+    config: Res<MarketConfig>,
+    workstations: Query<(&Workstation, &Owner)>,
+    mut businesses: Query<(Entity, &mut TradePolicy), With<Bussiness>>,
+) {
+    let mut targets: HashMap<Entity, HashMap<Good, i32>> = HashMap::new();
+    for (workstation, owner) in &workstations {
+        let business = targets.entry(owner.0).or_default();
+        for (good, amount) in &workstation.inputs {
+            *business.entry(*good).or_default() += amount * config.input_stock_items;
+        }
+    }
+    for (business, mut policy) in &mut businesses {
+        let inputs = targets.remove(&business).unwrap_or_default();
+        if policy.input_targets != inputs {
+            policy.input_targets = inputs;
+        }
+    }
+}
+
+fn apply_trade_policies(
+    market: Res<Market>,
+    mut query: Query<(&TradePolicy, &Storage, &mut MarketOrders)>,
+) {
+    for (policy, storage, mut orders) in &mut query {
+        orders.0.clear();
+        // Configured stock targets, raised to whatever the workstations need as inputs
+        let mut buy_below = policy.buy_below.clone();
+        for (good, target) in &policy.input_targets {
+            let entry = buy_below.entry(*good).or_default();
+            *entry = (*entry).max(*target);
+        }
+        for (good, keep) in &policy.sell_above {
+            // Stock bought as inputs is never sold on
+            let keep = policy.input_targets.get(good).map_or(*keep, |target| (*keep).max(*target));
+            let surplus = storage.amount(*good) - keep;
+            if surplus > 0 {
+                orders.0.push(Order { good: *good, side: OrderSide::Sell, quantity: surplus, limit_price: market.price(*good) * policy.sell_margin });
+            }
+        }
+        for (good, target) in &buy_below {
+            let missing = target - storage.amount(*good);
+            if missing > 0 {
+                orders.0.push(Order { good: *good, side: OrderSide::Buy, quantity: missing, limit_price: market.price(*good) * policy.buy_margin });
+            }
+        }
+    }
+}
+
+fn post_town_demand(
+    config: Res<MarketConfig>,
+    market: Res<Market>,
+    residents: Query<(), With<Health>>,
+    houses: Query<(), With<House>>,
+    mut town: Query<(&mut Storage, &mut MarketOrders), With<Town>>,
+) {
+    let Ok((mut storage, mut orders)) = town.single_mut() else { return; };
+    let population = residents.iter().count() as i32;
+    storage.money += population * config.town_income_per_resident;
+    // Households eat what they buy
+    storage.clear_goods();
+    orders.0.clear();
+    if population > 0 && !houses.is_empty() {
+        orders.0.push(Order {
+            good: Good::Food,
+            side: OrderSide::Buy,
+            quantity: population * config.town_food_per_resident,
+            limit_price: market.price(Good::Food) * 1.5,
+        });
+    }
+}
+
+fn clear_market(
+    config: Res<MarketConfig>,
+    mut market: ResMut<Market>,
+    mut traders: Query<(Entity, &mut Storage, &MarketOrders), Or<(With<Bussiness>, With<Town>)>>,
+    capacities: Query<&StorageCapacity>,
+    mut trades: EventWriter<TradeExecuted>,
+) {
+    market.tick += 1;
+    let tick = market.tick;
+
+    for good in Good::ALL {
+        let price = market.price(good);
+        let mut sellers: Vec<(Entity, i32, f32)> = Vec::new();
+        let mut buyers: Vec<(Entity, i32, f32)> = Vec::new();
+        let mut supply = 0;
+        let mut demand = 0;
+
+        for (entity, storage, orders) in &traders {
+            for order in orders.0.iter().filter(|o| o.good == good && o.quantity > 0) {
+                match order.side {
+                    OrderSide::Sell => {
+                        let quantity = order.quantity.min(storage.amount(good));
+                        supply += quantity;
+                        if order.limit_price <= price && quantity > 0 {
+                            sellers.push((entity, quantity, order.limit_price));
+                        }
+                    }
+                    OrderSide::Buy => {
+                        demand += order.quantity;
+                        let affordable = (storage.money as f32 / price.max(config.min_price)).floor() as i32;
+                        // Buyers never take more than their warehouse can hold
+                        let room = capacities.get(entity).map_or(i32::MAX, |c| c.room_for(&storage, good));
+                        let quantity = order.quantity.min(affordable).min(room);
+                        if order.limit_price >= price && quantity > 0 {
+                            buyers.push((entity, quantity, order.limit_price));
+                        }
+                    }
+                }
+            }
+        }
+
+        // Cheapest sellers fill the most eager buyers first, all at the current market price
+        sellers.sort_by(|a, b| a.2.total_cmp(&b.2));
+        buyers.sort_by(|a, b| b.2.total_cmp(&a.2));
+        let mut volume = 0;
+        let (mut si, mut bi) = (0, 0);
+        while si < sellers.len() && bi < buyers.len() {
+            let quantity = sellers[si].1.min(buyers[bi].1);
+            let (seller, buyer) = (sellers[si].0, buyers[bi].0);
+            let cost = (price * quantity as f32).round() as i32;
+            if seller != buyer {
+                if let Ok([(_, mut seller_storage, _), (_, mut buyer_storage, _)]) = traders.get_many_mut([seller, buyer]) {
+                    if buyer_storage.money >= cost && seller_storage.amount(good) >= quantity {
+                        // Goods change hands with their age
+                        let lots = seller_storage.take_batches(good, quantity);
+                        buyer_storage.money -= cost;
+                        buyer_storage.add_batches(good, lots);
+                        seller_storage.money += cost;
+                        volume += quantity;
+                        trades.write(TradeExecuted { good, seller, buyer, quantity, price });
+                    }
+                }
+            }
+            sellers[si].1 -= quantity;
+            buyers[bi].1 -= quantity;
+            if sellers[si].1 == 0 { si += 1; }
+            if bi < buyers.len() && buyers[bi].1 == 0 { bi += 1; }
+        }
+
+        let imbalance = (demand - supply) as f32 / (demand + supply).max(1) as f32;
+        let entry = market.goods.get_mut(&good).expect("every good has a market");
+        entry.price = (entry.price * (1.0 + config.price_adjust_rate * imbalance)).max(config.min_price);
+        entry.history.push_back(PricePoint { tick, price, supply, demand, volume });
+        while entry.history.len() > config.history_len {
+            entry.history.pop_front();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::ecs::system::RunSystemOnce;
+
+    fn market_world(config: MarketConfig) -> World {
+        let mut world = World::new();
+        world.insert_resource(Market::new(&config));
+        world.insert_resource(config);
+        world.init_resource::<Events<TradeExecuted>>();
+        world
+    }
+
+    fn order(side: OrderSide, quantity: i32, limit_price: f32) -> MarketOrders {
+        MarketOrders(vec![Order { good: Good::Food, side, quantity, limit_price }])
+    }
+
+    #[test]
+    fn excess_demand_trades_at_the_old_price_then_raises_it() {
+        let mut world = market_world(MarketConfig::default());
+        let mut stock = Storage::default();
+        stock.add(Good::Food, 2);
+        let seller = world.spawn((Bussiness, stock, order(OrderSide::Sell, 2, 0.0))).id();
+        let buyer = world.spawn((Town, Storage::with_money(1000), order(OrderSide::Buy, 10, 100.0))).id();
+
+        world.run_system_once(clear_market).unwrap();
+
+        assert_eq!(world.get::<Storage>(buyer).unwrap().amount(Good::Food), 2);
+        assert_eq!(world.get::<Storage>(seller).unwrap().money, 20);
+        let market = world.resource::<Market>();
+        let point = market.last(Good::Food).unwrap();
+        assert_eq!((point.tick, point.supply, point.demand, point.volume), (1, 2, 10, 2));
+        assert_eq!(point.price, 10.0);
+        // Imbalance of (10 - 2) / (10 + 2) at the default 5% rate
+        let expected = 10.0 * (1.0 + 0.05 * 8.0 / 12.0);
+        assert!((market.price(Good::Food) - expected).abs() < 1e-4);
+    }
+
+    #[test]
+    fn unmatched_supply_lowers_the_price_down_to_the_minimum() {
+        let config = MarketConfig { price_adjust_rate: 1.0, ..default() };
+        let mut world = market_world(config);
+        let mut stock = Storage::default();
+        stock.add(Good::Food, 5);
+        world.spawn((Bussiness, stock, order(OrderSide::Sell, 5, 0.0)));
+
+        world.run_system_once(clear_market).unwrap();
+
+        let market = world.resource::<Market>();
+        assert_eq!(market.price(Good::Food), 0.1);
+        assert_eq!(market.last(Good::Food).unwrap().volume, 0);
+        // Goods without orders keep their price
+        assert_eq!(market.price(Good::Wood), 6.0);
+    }
+
+    #[test]
+    fn history_keeps_only_the_latest_ticks() {
+        let config = MarketConfig { history_len: 3, ..default() };
+        let mut world = market_world(config);
+
+        for _ in 0..5 {
+            world.run_system_once(clear_market).unwrap();
+        }
+
+        let ticks: Vec<u64> = world.resource::<Market>().history(Good::Grain).map(|p| p.tick).collect();
+        assert_eq!(ticks, vec![3, 4, 5]);
+    }
+}
@@ -5,6 +5,7 @@ use bevy::prelude::*;
 pub struct CommonMaterials {
     pub hero: Handle<ColorMaterial>,
     pub food: Handle<ColorMaterial>,
+    pub water: Handle<ColorMaterial>,
     pub building: Handle<ColorMaterial>,
     pub green_half: Handle<ColorMaterial>,
     pub red_half: Handle<ColorMaterial>,
@@ -17,6 +18,7 @@ pub fn setup_common_materials(
 ) {
     let hero = materials.add(ColorMaterial::from(Color::hsl(200., 0.95, 0.5)));
     let food = materials.add(ColorMaterial::from(Color::hsl(21., 1., 0.356)));
+    let water = materials.add(ColorMaterial::from(Color::srgb(0.15, 0.35, 0.75)));
     // Keeping the original building color choice from the code
     let building = materials.add(ColorMaterial::from(Color::srgb(50., 50., 0.)));
 
@@ -27,6 +29,7 @@ pub fn setup_common_materials(
     commands.insert_resource(CommonMaterials {
         hero,
         food,
+        water,
         building,
         green_half,
         red_half,
@@ -0,0 +1,225 @@
+pub struct MetricsSystems;
+use crate::*;
+use crate::market::Market;
+use crate::notifications::Notification;
+use crate::ownership::Owner;
+use crate::spoilage::GoodsSpoiled;
+use crate::production::{Bussiness, Good, GoodsProduced, Storage, Workstation};
+use bevy::prelude::*;
+use bevy::ecs::schedule::common_conditions::on_event;
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::fmt::Write as _;
+use std::path::Path;
+
+impl Plugin for MetricsSystems {
+    fn build(&self, app: &mut App) {
+        app
+            .insert_resource(MetricsConfig::default())
+            .init_resource::<Metrics>()
+            // Sampled after Update so every tick system has already run this frame
+            .add_systems(PostUpdate, record_metrics.run_if(on_event::<WorldTick>))
+            .add_systems(Update, export_on_key)
+            // Before Last, where the notification bus reads the export result one final time
+            .add_systems(PostUpdate, export_on_exit.after(record_metrics));
+    }
+}
+
+#[derive(Resource, Debug, Clone)]
+pub struct MetricsConfig {
+    // Samples kept per series, older ones are dropped
+    pub capacity: usize,
+}
+
+impl Default for MetricsConfig {
+    fn default() -> Self {
+        Self {
+            capacity: 4096,
+        }
+    }
+}
+
+// Fixed-size ring buffer of (tick, value) samples
+#[derive(Debug, Clone, Default)]
+pub struct Series {
+    pub points: VecDeque<(u64, f32)>,
+}
+
+impl Series {
+    pub fn last(&self) -> Option<f32> {
+        self.points.back().map(|(_, v)| *v)
+    }
+}
+
+// Time series of the economy, keyed by names such as "price/Food" or "business/<name>/money"
+#[derive(Resource, Debug, Default)]
+pub struct Metrics {
+    series: BTreeMap<String, Series>,
+    tick: u64,
+}
+
+impl Metrics {
+    pub fn business_key(business: &str, metric: &str) -> String {
+        format!("business/{}/{}", business, metric)
+    }
+
+    pub fn tick(&self) -> u64 {
+        self.tick
+    }
+
+    pub fn record(&mut self, name: impl Into<String>, value: f32, capacity: usize) {
+        let series = self.series.entry(name.into()).or_default();
+        series.points.push_back((self.tick, value));
+        while series.points.len() > capacity {
+            series.points.pop_front();
+        }
+    }
+
+    pub fn series(&self, name: &str) -> Option<&Series> {
+        self.series.get(name)
+    }
+
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.series.keys().map(|k| k.as_str())
+    }
+
+    // Long format, one row per sample: tick,metric,value
+    pub fn to_csv(&self) -> String {
+        let mut out = String::from("tick,metric,value\n");
+        for (name, series) in &self.series {
+            for (tick, value) in &series.points {
+                let _ = writeln!(out, "{},\"{}\",{}", tick, name.replace('"', "\"\""), value);
+            }
+        }
+        out
+    }
+
+    // {"metric": [[tick, value], ...], ...}
+    pub fn to_json(&self) -> String {
+        let mut out = String::from("{");
+        for (i, (name, series)) in self.series.iter().enumerate() {
+            if i > 0 { out.push(','); }
+            let _ = write!(out, "\n  \"{}\": [", name.replace('\\', "\\\\").replace('"', "\\\""));
+            for (j, (tick, value)) in series.points.iter().enumerate() {
+                if j > 0 { out.push(','); }
+                if value.is_finite() {
+                    let _ = write!(out, "[{},{}]", tick, value);
+                } else {
+                    let _ = write!(out, "[{},null]", tick);
+                }
+            }
+            out.push(']');
+        }
+        out.push_str("\n}\n");
+        out
+    }
+
+    // Writes metrics.csv and metrics.json into `dir`
+    pub fn export(&self, dir: &Path) -> std::io::Result<()> {
+        std::fs::create_dir_all(dir)?;
+        std::fs::write(dir.join("metrics.csv"), self.to_csv())?;
+        std::fs::write(dir.join("metrics.json"), self.to_json())?;
+        Ok(())
+    }
+}
+
+fn record_metrics(
+    config: Res<MetricsConfig>,
+    mut metrics: ResMut<Metrics>,
+    mut produced: EventReader<GoodsProduced>,
+    mut spoiled: EventReader<GoodsSpoiled>,
+    market: Res<Market>,
+    businesses: Query<(Entity, Option<&EntityLabel>, &Storage), With<Bussiness>>,
+    buffers: Query<(&Storage, &Owner), With<Workstation>>,
+    characters: Query<(&Health, &Hunger, &Thirst, &Sleep)>,
+) {
+    metrics.tick += 1;
+    let capacity = config.capacity;
+
+    let mut production: HashMap<Good, i32> = HashMap::new();
+    let mut business_production: HashMap<(Entity, Good), i32> = HashMap::new();
+    for event in produced.read() {
+        *production.entry(event.good).or_default() += event.amount;
+        *business_production.entry((event.business, event.good)).or_default() += event.amount;
+    }
+
+    let mut spoilage: HashMap<Good, i32> = HashMap::new();
+    let mut business_spoilage: HashMap<(Entity, Good), i32> = HashMap::new();
+    for event in spoiled.read() {
+        *spoilage.entry(event.good).or_default() += event.amount;
+        if let Some(owner) = event.owner {
+            *business_spoilage.entry((owner, event.good)).or_default() += event.amount;
+        }
+    }
+
+    // Output still waiting in workstation buffers counts towards its business' stock
+    let mut buffered: HashMap<(Entity, Good), i32> = HashMap::new();
+    for (storage, owner) in &buffers {
+        for (good, amount) in &storage.goods {
+            *buffered.entry((owner.0, *good)).or_default() += amount;
+        }
+    }
+
+    let mut total_stock: HashMap<Good, i32> = HashMap::new();
+    for (business, label, storage) in &businesses {
+        let name = label.map_or_else(|| format!("{:?}", business), |l| l.0.clone());
+        metrics.record(Metrics::business_key(&name, "money"), storage.money as f32, capacity);
+        for good in Good::ALL {
+            let stock = storage.amount(good) + buffered.get(&(business, good)).copied().unwrap_or(0);
+            *total_stock.entry(good).or_default() += stock;
+            metrics.record(Metrics::business_key(&name, &format!("stock/{}", good.name())), stock as f32, capacity);
+            let made = business_production.get(&(business, good)).copied().unwrap_or(0);
+            metrics.record(Metrics::business_key(&name, &format!("production/{}", good.name())), made as f32, capacity);
+            let lost = business_spoilage.get(&(business, good)).copied().unwrap_or(0);
+            metrics.record(Metrics::business_key(&name, &format!("spoiled/{}", good.name())), lost as f32, capacity);
+        }
+    }
+
+    for good in Good::ALL {
+        metrics.record(format!("production/{}", good.name()), production.get(&good).copied().unwrap_or(0) as f32, capacity);
+        metrics.record(format!("stock/{}", good.name()), total_stock.get(&good).copied().unwrap_or(0) as f32, capacity);
+        metrics.record(format!("price/{}", good.name()), market.price(good), capacity);
+        metrics.record(format!("spoiled/{}", good.name()), spoilage.get(&good).copied().unwrap_or(0) as f32, capacity);
+    }
+
+    let population = characters.iter().count();
+    metrics.record("population", population as f32, capacity);
+    if population > 0 {
+        let n = population as f32;
+        let (health, hunger, thirst, sleep) = characters.iter().fold((0.0, 0.0, 0.0, 0.0), |acc, (h, hu, th, sl)| {
+            (acc.0 + h.0, acc.1 + hu.value, acc.2 + th.value, acc.3 + sl.value)
+        });
+        metrics.record("needs/health", health / n, capacity);
+        metrics.record("needs/hunger", hunger / n, capacity);
+        metrics.record("needs/thirst", thirst / n, capacity);
+        metrics.record("needs/sleep", sleep / n, capacity);
+    }
+}
+
+fn export_metrics(metrics: &Metrics, options: &RunOptions) -> Notification {
+    match metrics.export(&options.metrics_out) {
+        Ok(()) => Notification::info("Metrics", format!("Exported {} ticks to {}", metrics.tick(), options.metrics_out.display())),
+        Err(err) => Notification::warning("Metrics", format!("Export to {} failed: {}", options.metrics_out.display(), err)),
+    }
+}
+
+fn export_on_key(
+    actions: Res<Actions>,
+    options: Res<RunOptions>,
+    metrics: Res<Metrics>,
+    mut notifications: EventWriter<Notification>,
+) {
+    if actions.just_pressed(Action::ExportMetrics) {
+        notifications.write(export_metrics(&metrics, &options));
+    }
+}
+
+fn export_on_exit(
+    mut exits: EventReader<AppExit>,
+    options: Res<RunOptions>,
+    metrics: Res<Metrics>,
+    mut notifications: EventWriter<Notification>,
+) {
+    if exits.read().next().is_some() && (options.headless || options.export_on_exit) {
+        notifications.write(export_metrics(&metrics, &options));
+    }
+}
@@ -0,0 +1,253 @@
+pub struct MinimapSystems;
+use crate::*;
+use crate::world_grid::{TERRAIN_FARM, TERRAIN_WATER};
+use bevy::image::ImageSampler;
+use bevy::render::render_asset::RenderAssetUsages;
+use bevy::render::render_resource::{Extent3d, TextureDimension, TextureFormat};
+use bevy::sprite::Anchor;
+
+impl Plugin for MinimapSystems {
+    fn build(&self, app: &mut App) {
+        app
+            .insert_resource(MinimapConfig::default())
+            .add_systems(Startup, setup_minimap)
+            // The UI hangs off the camera spawned in Startup
+            .add_systems(PostStartup, spawn_minimap_ui)
+            .add_systems(Update, (update_minimap_terrain, update_minimap_agents, draw_minimap_viewport).chain());
+    }
+}
+
+#[derive(Resource, Debug, Clone, Copy)]
+pub struct MinimapConfig {
+    // Texture side in pixels for the longer grid side; bigger grids share pixels between cells
+    pub resolution: u32,
+    // On-screen side length in pixels
+    pub panel_size: f32,
+    // Seconds between redraws of the agent dots
+    pub agent_refresh: f32,
+}
+
+impl Default for MinimapConfig {
+    fn default() -> Self {
+        Self {
+            resolution: 256,
+            panel_size: 200.0,
+            agent_refresh: 0.25,
+        }
+    }
+}
+
+// Downscaled copy of the grid: `base` holds terrain colours, the image adds agents on top
+#[derive(Resource)]
+struct Minimap {
+    image: Handle<Image>,
+    width: u32,
+    height: u32,
+    cells_per_pixel: u32,
+    base: Vec<[u8; 4]>,
+    // Grid terrain log position already drawn, None before the first full draw
+    log_position: Option<usize>,
+    // Pixels currently covered by agent dots
+    agent_pixels: Vec<usize>,
+    since_agents: f32,
+}
+
+#[derive(Component)]
+struct MinimapPanel;
+
+// Buildings win over fields, fields over water when a pixel covers several cells
+fn terrain_color(terrain_type: u8) -> ([u8; 4], u8) {
+    match terrain_type {
+        0 => ([40, 70, 40, 255], 0),
+        TERRAIN_WATER => ([40, 80, 160, 255], 1),
+        TERRAIN_FARM => ([150, 120, 50, 255], 2),
+        _ => ([170, 170, 170, 255], 3),
+    }
+}
+
+fn setup_minimap(
+    mut commands: Commands,
+    mut images: ResMut<Assets<Image>>,
+    config: Res<MinimapConfig>,
+    grid: Res<WorldGrid>,
+) {
+    let longest = grid.width().max(grid.height());
+    let cells_per_pixel = longest.div_ceil(config.resolution.max(1)).max(1);
+    let (width, height) = (grid.width().div_ceil(cells_per_pixel), grid.height().div_ceil(cells_per_pixel));
+    let mut image = Image::new_fill(
+        Extent3d { width, height, depth_or_array_layers: 1 },
+        TextureDimension::D2,
+        &[0, 0, 0, 255],
+        TextureFormat::Rgba8UnormSrgb,
+        RenderAssetUsages::default(),
+    );
+    image.sampler = ImageSampler::nearest();
+    commands.insert_resource(Minimap {
+        image: images.add(image),
+        width,
+        height,
+        cells_per_pixel,
+        base: vec![[0, 0, 0, 255]; (width * height) as usize],
+        log_position: None,
+        agent_pixels: Vec::new(),
+        since_agents: 0.0,
+    });
+}
+
+fn spawn_minimap_ui(
+    mut commands: Commands,
+    config: Res<MinimapConfig>,
+    minimap: Res<Minimap>,
+    camera_q: Query<Entity, With<MainCamera>>,
+) {
+    let Ok(camera) = camera_q.single() else { return; };
+    commands.entity(camera).with_children(|cam| {
+        cam.spawn((
+            Name::new("Minimap Root"),
+            UiLayoutRoot::new_2d(),
+            UiFetchFromCamera::<0>,
+        ))
+        .with_children(|ui| {
+            ui.spawn((
+                Name::new("Minimap"),
+                UiLayout::window()
+                    .anchor(Anchor::TopRight)
+                    .pos(Rl((99.0, 1.0)))
+                    .size(Ab((config.panel_size, config.panel_size)))
+                    .pack(),
+                Sprite { image: minimap.image.clone(), custom_size: Some(Vec2::splat(config.panel_size)), ..default() },
+                OnHoverSetCursor::new(SystemCursorIcon::Crosshair),
+                MinimapPanel,
+            ))
+            .observe(|_: Trigger<Pointer<Over>>, mut cnt: ResMut<UiBlockHoverCount>| {
+                cnt.0 += 1;
+            })
+            .observe(|_: Trigger<Pointer<Out>>, mut cnt: ResMut<UiBlockHoverCount>| {
+                if cnt.0 > 0 { cnt.0 -= 1; }
+            })
+            .observe(move_camera_to_click);
+        });
+    });
+}
+
+// Centres the camera on the clicked point of the map
+fn move_camera_to_click(
+    trigger: Trigger<Pointer<Click>>,
+    grid: Res<WorldGrid>,
+    panels: Query<(&GlobalTransform, &Sprite), With<MinimapPanel>>,
+    mut cameras: Query<&mut Transform, With<MainCamera>>,
+) {
+    let Some(hit) = trigger.event().hit.position else { return; };
+    let Ok((panel_transform, sprite)) = panels.get(trigger.target()) else { return; };
+    let Some(size) = sprite.custom_size else { return; };
+    let Ok(mut camera) = cameras.single_mut() else { return; };
+    // Share of the panel from its centre, -0.5..0.5 on both axes
+    let uv = (hit.truncate() - panel_transform.translation().truncate()) / size;
+    let world = Vec2::new(grid.width() as f32, grid.height() as f32) * grid.scale() as f32;
+    let target = (uv * world).clamp(-world / 2.0, world / 2.0);
+    camera.translation = target.extend(camera.translation.z);
+}
+
+// Recolours the pixel covering `cell` from all the cells it stands for
+fn redraw_pixel(minimap: &mut Minimap, grid: &WorldGrid, cell: Vec2) {
+    let cpp = minimap.cells_per_pixel;
+    let (px, py) = (cell.x as u32 / cpp, cell.y as u32 / cpp);
+    let mut best = terrain_color(0);
+    for y in py * cpp..((py + 1) * cpp).min(grid.height()) {
+        for x in px * cpp..((px + 1) * cpp).min(grid.width()) {
+            let terrain = grid.tile(Vec2::new(x as f32, y as f32)).map_or(0, |t| t.terrain_type);
+            let candidate = terrain_color(terrain);
+            if candidate.1 > best.1 { best = candidate; }
+        }
+    }
+    // Image rows run top-down, grid rows bottom-up
+    let index = ((minimap.height - 1 - py) * minimap.width + px) as usize;
+    minimap.base[index] = best.0;
+}
+
+fn write_pixels(image: &mut Image, pixels: impl Iterator<Item = (usize, [u8; 4])>) {
+    let Some(data) = image.data.as_mut() else { return; };
+    for (index, color) in pixels {
+        data[index * 4..index * 4 + 4].copy_from_slice(&color);
+    }
+}
+
+// Only pixels whose cells show up in the grid's terrain log are redrawn
+fn update_minimap_terrain(
+    grid: Res<WorldGrid>,
+    mut minimap: ResMut<Minimap>,
+    mut images: ResMut<Assets<Image>>,
+) {
+    let end = grid.terrain_log_end();
+    if minimap.log_position == Some(end) { return; }
+
+    let changed: Vec<Vec2> = match minimap.log_position.and_then(|p| grid.terrain_changes_since(p)) {
+        Some(cells) => cells.collect(),
+        // First draw, or too far behind the log: every pixel
+        None => {
+            let cpp = minimap.cells_per_pixel as f32;
+            (0..minimap.height)
+                .flat_map(|y| (0..minimap.width).map(move |x| Vec2::new(x as f32 * cpp, y as f32 * cpp)))
+                .collect()
+        }
+    };
+    let mut touched = Vec::with_capacity(changed.len());
+    for cell in changed {
+        redraw_pixel(&mut minimap, &grid, cell);
+        let cpp = minimap.cells_per_pixel;
+        touched.push(((minimap.height - 1 - cell.y as u32 / cpp) * minimap.width + cell.x as u32 / cpp) as usize);
+    }
+    minimap.log_position = Some(end);
+
+    let Some(image) = images.get_mut(&minimap.image) else { return; };
+    // Agent dots stay on top until their next refresh
+    let base = &minimap.base;
+    let agents = &minimap.agent_pixels;
+    write_pixels(image, touched.into_iter().filter(|i| !agents.contains(i)).map(|i| (i, base[i])));
+}
+
+// Puts back the terrain under last refresh's dots and draws every character at its current cell
+fn update_minimap_agents(
+    config: Res<MinimapConfig>,
+    time: Res<Time<Real>>,
+    grid: Res<WorldGrid>,
+    mut minimap: ResMut<Minimap>,
+    mut images: ResMut<Assets<Image>>,
+    agents: Query<&Transform, With<Health>>,
+) {
+    minimap.since_agents += time.delta_secs();
+    if minimap.since_agents < config.agent_refresh { return; }
+    minimap.since_agents = 0.0;
+
+    let cpp = minimap.cells_per_pixel;
+    let (width, height) = (minimap.width, minimap.height);
+    let now: Vec<usize> = agents
+        .iter()
+        .map(|t| grid.world_to_grid(t.translation.truncate()))
+        .filter(|cell| cell.x >= 0.0 && cell.y >= 0.0 && (cell.x as u32) < grid.width() && (cell.y as u32) < grid.height())
+        .map(|cell| ((height - 1 - cell.y as u32 / cpp) * width + cell.x as u32 / cpp) as usize)
+        .collect();
+    let previous = std::mem::replace(&mut minimap.agent_pixels, now);
+
+    let Some(image) = images.get_mut(&minimap.image) else { return; };
+    let restore = previous.into_iter().map(|i| (i, minimap.base[i]));
+    let dots = minimap.agent_pixels.iter().map(|i| (*i, [255, 240, 120, 255]));
+    write_pixels(image, restore.chain(dots));
+}
+
+// Outline of what the camera currently shows, drawn over the panel
+fn draw_minimap_viewport(
+    grid: Res<WorldGrid>,
+    windows: Query<&Window>,
+    cameras: Query<(&Transform, &Projection), With<MainCamera>>,
+    panels: Query<(&GlobalTransform, &Sprite), With<MinimapPanel>>,
+    mut gizmos: Gizmos,
+) {
+    let (Ok(window), Ok((camera, projection)), Ok((panel_transform, sprite))) = (windows.single(), cameras.single(), panels.single()) else { return; };
+    let Some(size) = sprite.custom_size else { return; };
+    let Projection::Orthographic(ortho) = projection else { return; };
+    let world = Vec2::new(grid.width() as f32, grid.height() as f32) * grid.scale() as f32;
+    let centre = panel_transform.translation().truncate() + camera.translation.truncate() / world * size;
+    let view = (window.size() * ortho.scale / world * size).min(size);
+    gizmos.rect_2d(centre, view, Color::WHITE);
+}
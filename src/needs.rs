@@ -0,0 +1,479 @@
+pub struct NeedsSystems;
+use crate::*;
+use crate::notifications::Notification;
+use crate::production::{Good, Storage};
+use crate::world_grid::TERRAIN_WATER;
+use bevy::prelude::*;
+use bevy::ecs::schedule::common_conditions::on_event;
+use std::collections::HashMap;
+use serde::Deserialize;
+
+const NEEDS_CONFIG_PATH: &str = "assets/config/needs.ron";
+
+impl Plugin for NeedsSystems {
+    fn build(&self, app: &mut App) {
+        app
+            .insert_resource(NeedsConfig::default())
+            .register_need::<HungerNeed>()
+            .register_need::<ThirstNeed>()
+            .register_need::<SleepNeed>()
+            .add_event::<NeedInteraction>()
+            .add_event::<CharacterDied>()
+            // Needs `CommonMaterials` for the well meshes
+            .add_systems(PostStartup, spawn_water)
+            .add_systems(Update, (collisions_to_interactions, apply_need_interactions).chain())
+            // After food search so a thirsty character picks water over a nearby meal
+            .add_systems(Update, seek_water.after(food_search))
+            .add_systems(Update, (drink_from_water_tiles, restore_sleep, update_health).run_if(on_event::<WorldTick>))
+            .add_systems(Update, warn_critical_needs.run_if(on_event::<WorldTick>))
+            // Despawn late so every Update system can react to `CharacterDied` while the entity still exists
+            .add_systems(PostUpdate, (notify_deaths, despawn_dead_characters).chain());
+    }
+}
+
+pub trait NeedAppExt {
+    // Adds decay for `Need<K>` on every `WorldTick`, falling back to default bounds when the config has no entry for it
+    fn register_need<K: NeedKind>(&mut self) -> &mut Self;
+}
+
+impl NeedAppExt for App {
+    fn register_need<K: NeedKind>(&mut self) -> &mut Self {
+        self.world_mut()
+            .get_resource_or_insert_with(|| NeedDefinitions::load(NEEDS_CONFIG_PATH))
+            .0
+            .entry(K::KEY.to_string())
+            .or_default();
+        self.add_systems(Update, decay_need::<K>.run_if(on_event::<WorldTick>))
+    }
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+pub enum DecayCurve {
+    // Subtracts a flat amount per tick
+    Linear(f32),
+    // Subtracts a fraction of the current value per tick
+    Exponential(f32),
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct NeedDefinition {
+    pub curve: DecayCurve,
+    #[serde(default)]
+    pub activity_multipliers: HashMap<Activity, f32>,
+    pub min: f32,
+    pub max: f32,
+}
+
+impl Default for NeedDefinition {
+    fn default() -> Self {
+        Self {
+            curve: DecayCurve::Linear(1.0),
+            activity_multipliers: HashMap::default(),
+            min: 0.0,
+            max: 100.0,
+        }
+    }
+}
+
+impl NeedDefinition {
+    pub fn decay(&self, value: f32, activity: Activity, rate_scale: f32) -> f32 {
+        let multiplier = self.activity_multipliers.get(&activity).copied().unwrap_or(1.0) * rate_scale;
+        let decayed = match self.curve {
+            DecayCurve::Linear(rate) => value - rate * multiplier,
+            DecayCurve::Exponential(rate) => value - value * rate * multiplier,
+        };
+        self.clamp(decayed)
+    }
+
+    pub fn clamp(&self, value: f32) -> f32 {
+        value.clamp(self.min, self.max)
+    }
+}
+
+// Need definitions keyed by `NeedKind::KEY`, read from `assets/config/needs.ron`
+#[derive(Resource, Debug, Clone, Default, Deserialize)]
+#[serde(transparent)]
+pub struct NeedDefinitions(pub HashMap<String, NeedDefinition>);
+
+impl NeedDefinitions {
+    pub fn load(path: &str) -> Self {
+        let Ok(text) = std::fs::read_to_string(path) else {
+            warn!("[needs] No config at {path}, using default need definitions");
+            return Self::default();
+        };
+        match ron::from_str(&text) {
+            Ok(defs) => defs,
+            Err(e) => {
+                warn!("[needs] Failed to parse {path}: {e}, using default need definitions");
+                Self::default()
+            }
+        }
+    }
+
+    pub fn get<K: NeedKind>(&self) -> &NeedDefinition {
+        &self.0[K::KEY]
+    }
+}
+
+// Restore rates and health thresholds. Need bounds live in `NeedDefinitions`.
+#[derive(Resource, Debug, Clone, Copy)]
+pub struct NeedsConfig {
+    // Hunger restored by one unit of food taken out of a `Storage`
+    pub storage_meal_nutrition: f32,
+    // Thirst restored per tick while standing on a water tile
+    pub water_tile_per_tick: f32,
+    // Thirst restored by one visit to a well
+    pub well_water: f32,
+    // Idle characters below this thirst walk to the nearest water
+    pub seek_water_below: f32,
+    // Sleep restored per tick while in a bed
+    pub sleep_per_tick: f32,
+    pub max_health: f32,
+    // A need at or below this value damages health every tick
+    pub critical_threshold: f32,
+    // All needs above this value let health recover
+    pub satisfied_threshold: f32,
+    pub critical_damage_per_tick: f32,
+    pub recovery_per_tick: f32,
+}
+
+impl Default for NeedsConfig {
+    fn default() -> Self {
+        Self {
+            storage_meal_nutrition: 30.0,
+            water_tile_per_tick: 10.0,
+            well_water: 60.0,
+            seek_water_below: 40.0,
+            sleep_per_tick: 5.0,
+            max_health: 100.0,
+            critical_threshold: 10.0,
+            satisfied_threshold: 50.0,
+            critical_damage_per_tick: 2.0,
+            recovery_per_tick: 0.5,
+        }
+    }
+}
+
+// A character consuming something. `source` is the food item, storage, well or bed.
+// Other systems can write these directly, e.g. to make a worker eat from its business storage.
+#[derive(Event, Debug, Clone, Copy)]
+pub enum NeedInteraction {
+    Eat { consumer: Entity, source: Entity },
+    Drink { consumer: Entity, source: Entity },
+    Sleep { consumer: Entity, bed: Entity },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DeathCause {
+    Starvation,
+    Dehydration,
+    Exhaustion,
+}
+
+impl DeathCause {
+    pub fn describe(&self) -> &'static str {
+        match self {
+            DeathCause::Starvation => "starved to death",
+            DeathCause::Dehydration => "died of thirst",
+            DeathCause::Exhaustion => "died of exhaustion",
+        }
+    }
+
+    pub fn warning(&self) -> &'static str {
+        match self {
+            DeathCause::Starvation => "is starving",
+            DeathCause::Dehydration => "is dying of thirst",
+            DeathCause::Exhaustion => "is collapsing from exhaustion",
+        }
+    }
+}
+
+// Written once when a character's health reaches zero. The entity is despawned in `PostUpdate`,
+// so subsystems holding references to it (jobs, homes, reservations) should clean up on this event.
+#[derive(Event, Debug, Clone, Copy)]
+pub struct CharacterDied {
+    pub entity: Entity,
+    pub cause: DeathCause,
+}
+
+fn collisions_to_interactions(
+    mut commands: Commands,
+    mut collision_events: EventReader<CollisionEvent>,
+    mut interactions: EventWriter<NeedInteraction>,
+    consumers: Query<(), With<Hunger>>,
+    food_sources: Query<(), Or<(With<Food>, With<Storage>)>>,
+    water_sources: Query<(), With<WaterSource>>,
+    beds: Query<(), With<Bed>>,
+    sleeping: Query<&Asleep>,
+) {
+    for collision_event in collision_events.read() {
+        match collision_event {
+            CollisionEvent::Started(e1, e2, _flags) => {
+                for (consumer, other) in [(*e1, *e2), (*e2, *e1)] {
+                    if consumers.get(consumer).is_err() { continue; }
+                    if food_sources.get(other).is_ok() {
+                        interactions.write(NeedInteraction::Eat { consumer, source: other });
+                    } else if water_sources.get(other).is_ok() {
+                        interactions.write(NeedInteraction::Drink { consumer, source: other });
+                    } else if beds.get(other).is_ok() {
+                        interactions.write(NeedInteraction::Sleep { consumer, bed: other });
+                    }
+                }
+            }
+            CollisionEvent::Stopped(e1, e2, _flags) => {
+                // Leaving the bed wakes the character up
+                for (consumer, other) in [(*e1, *e2), (*e2, *e1)] {
+                    if let Ok(sleep) = sleeping.get(consumer) {
+                        if sleep.bed == other {
+                            commands.entity(consumer).remove::<Asleep>();
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn apply_need_interactions(
+    mut commands: Commands,
+    mut interactions: EventReader<NeedInteraction>,
+    config: Res<NeedsConfig>,
+    defs: Res<NeedDefinitions>,
+    mut hunger_query: Query<&mut Hunger>,
+    mut thirst_query: Query<&mut Thirst>,
+    sleep_query: Query<&Sleep>,
+    food_query: Query<&Food>,
+    mut storage_query: Query<&mut Storage>,
+    water_query: Query<&WaterSource>,
+) {
+    for interaction in interactions.read() {
+        match *interaction {
+            NeedInteraction::Eat { consumer, source } => {
+                let Ok(mut hunger) = hunger_query.get_mut(consumer) else { continue; };
+                let def = defs.get::<HungerNeed>();
+                if hunger.value >= def.max { continue; }
+                if let Ok(food) = food_query.get(source) {
+                    hunger.value = def.clamp(hunger.value + food.0);
+                    commands.entity(source).despawn();
+                } else if let Ok(mut storage) = storage_query.get_mut(source) {
+                    if storage.take(Good::Food, 1) {
+                        hunger.value = def.clamp(hunger.value + config.storage_meal_nutrition);
+                    }
+                }
+            }
+            NeedInteraction::Drink { consumer, source } => {
+                let Ok(mut thirst) = thirst_query.get_mut(consumer) else { continue; };
+                if let Ok(water) = water_query.get(source) {
+                    thirst.value = defs.get::<ThirstNeed>().clamp(thirst.value + water.0);
+                }
+            }
+            NeedInteraction::Sleep { consumer, bed } => {
+                let Ok(sleep) = sleep_query.get(consumer) else { continue; };
+                if sleep.value < defs.get::<SleepNeed>().max {
+                    commands.entity(consumer).insert(Asleep { bed });
+                }
+            }
+        }
+    }
+}
+
+// Wells around the starting area, in cells from the grid centre
+const STARTING_WELLS: [Vec2; 3] = [Vec2::new(-6.0, -4.0), Vec2::new(5.0, 9.0), Vec2::new(-10.0, 8.0)];
+
+// Draws the ponds `WorldGrid` was generated with and digs the starting wells
+fn spawn_water(
+    mut commands: Commands,
+    config: Res<NeedsConfig>,
+    mut grid: ResMut<WorldGrid>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    common_materials: Res<CommonMaterials>,
+) {
+    let scale = grid.scale() as f32;
+    let cells: Vec<Vec2> = (0..grid.height())
+        .flat_map(|y| (0..grid.width()).map(move |x| Vec2::new(x as f32, y as f32)))
+        .filter(|cell| grid.tile(*cell).is_some_and(|t| t.terrain_type == TERRAIN_WATER))
+        .collect();
+    for cell in cells {
+        let pos = grid.grid_to_world(cell, Vec2::ONE);
+        commands.spawn((
+            Sprite::from_color(Color::srgb(0.15, 0.35, 0.75), Vec2::splat(scale)),
+            Transform::from_xyz(pos.x, pos.y, 0.01),
+            Pickable::IGNORE,
+        ));
+    }
+
+    let centre = Vec2::new((grid.width() / 2) as f32, (grid.height() / 2) as f32);
+    let mesh = meshes.add(Mesh::from(Circle::new(8.0)));
+    for offset in STARTING_WELLS {
+        let cell = centre + offset;
+        if !grid.is_area_free(cell, Vec2::ONE) { continue; }
+        // Claimed like a building so nothing gets placed on top
+        grid.modify_rectangle(cell, Vec2::ONE);
+        let pos = grid.grid_to_world(cell, Vec2::ONE);
+        commands.spawn((
+            EntityLabel("Well".to_string()),
+            WaterSource(config.well_water),
+            VisualBundle {
+                mesh: Mesh2d(mesh.clone()),
+                material: MeshMaterial2d(common_materials.water.clone()),
+                transform: Transform::from_xyz(pos.x, pos.y, 0.0),
+            },
+            CollisionBundle::circle_sensor(8.0, RigidBody::Fixed, false),
+        ));
+    }
+}
+
+// Thirsty idle characters head for the nearest well or water tile
+fn seek_water(
+    config: Res<NeedsConfig>,
+    grid: Res<WorldGrid>,
+    // Centres of water tiles and the terrain log position they were collected at
+    mut water_tiles: Local<(Option<usize>, Vec<Vec2>)>,
+    wells: Query<&Transform, With<WaterSource>>,
+    mut query: Query<(&Transform, &Thirst, &mut Destination), (Without<CurrentJob>, Without<Asleep>)>,
+) {
+    if water_tiles.0 != Some(grid.terrain_log_end()) {
+        water_tiles.1 = (0..grid.height())
+            .flat_map(|y| (0..grid.width()).map(move |x| Vec2::new(x as f32, y as f32)))
+            .filter(|cell| grid.tile(*cell).is_some_and(|t| t.terrain_type == TERRAIN_WATER))
+            .map(|cell| grid.grid_to_world(cell, Vec2::ONE))
+            .collect();
+        water_tiles.0 = Some(grid.terrain_log_end());
+    }
+    for (transform, thirst, mut destination) in &mut query {
+        if thirst.value >= config.seek_water_below { continue; }
+        let origin = transform.translation.truncate();
+        let nearest = wells
+            .iter()
+            .map(|t| t.translation.truncate())
+            .chain(water_tiles.1.iter().copied())
+            .min_by(|a, b| a.distance_squared(origin).total_cmp(&b.distance_squared(origin)));
+        if let Some(target) = nearest {
+            destination.0 = target;
+        }
+    }
+}
+
+fn drink_from_water_tiles(
+    grid: Res<WorldGrid>,
+    config: Res<NeedsConfig>,
+    defs: Res<NeedDefinitions>,
+    mut query: Query<(&Transform, &mut Thirst)>,
+) {
+    for (transform, mut thirst) in &mut query {
+        let Some(tile) = grid.tile_at_world(transform.translation.truncate()) else { continue; };
+        if tile.terrain_type == TERRAIN_WATER {
+            thirst.value = defs.get::<ThirstNeed>().clamp(thirst.value + config.water_tile_per_tick);
+        }
+    }
+}
+
+fn decay_need<K: NeedKind>(
+    defs: Res<NeedDefinitions>,
+    mut query: Query<(&mut Need<K>, Option<&Activity>, Has<Asleep>)>,
+) {
+    let def = defs.get::<K>();
+    for (mut need, activity, asleep) in &mut query {
+        let activity = if asleep { Activity::Sleeping } else { activity.copied().unwrap_or_default() };
+        need.value = def.decay(need.value, activity, need.rate_scale);
+    }
+}
+
+fn restore_sleep(
+    mut commands: Commands,
+    config: Res<NeedsConfig>,
+    defs: Res<NeedDefinitions>,
+    mut query: Query<(Entity, &mut Sleep), With<Asleep>>,
+) {
+    let def = defs.get::<SleepNeed>();
+    for (entity, mut sleep) in &mut query {
+        sleep.value = def.clamp(sleep.value + config.sleep_per_tick);
+        if sleep.value >= def.max {
+            commands.entity(entity).remove::<Asleep>();
+        }
+    }
+}
+
+fn update_health(
+    config: Res<NeedsConfig>,
+    mut died: EventWriter<CharacterDied>,
+    mut query: Query<(Entity, &mut Health, &Hunger, &Thirst, &Sleep)>,
+) {
+    for (entity, mut health, hunger, thirst, sleep) in &mut query {
+        if health.0 <= 0.0 { continue; }
+        let critical: Vec<(f32, DeathCause)> = [
+            (hunger.value, DeathCause::Starvation),
+            (thirst.value, DeathCause::Dehydration),
+            (sleep.value, DeathCause::Exhaustion),
+        ]
+            .into_iter()
+            .filter(|(value, _)| *value <= config.critical_threshold)
+            .collect();
+
+        if !critical.is_empty() {
+            health.0 -= config.critical_damage_per_tick * critical.len() as f32;
+        } else if hunger.value > config.satisfied_threshold
+            && thirst.value > config.satisfied_threshold
+            && sleep.value > config.satisfied_threshold {
+            health.0 = (health.0 + config.recovery_per_tick).min(config.max_health);
+        }
+
+        if health.0 <= 0.0 {
+            health.0 = 0.0;
+            // Attribute the death to the most depleted need
+            let cause = critical
+                .iter()
+                .min_by(|a, b| a.0.total_cmp(&b.0))
+                .map_or(DeathCause::Starvation, |(_, cause)| *cause);
+            died.write(CharacterDied { entity, cause });
+        }
+    }
+}
+
+fn despawn_dead_characters(
+    mut commands: Commands,
+    mut died: EventReader<CharacterDied>,
+) {
+    for ev in died.read() {
+        if let Ok(mut entity) = commands.get_entity(ev.entity) {
+            entity.despawn();
+        }
+    }
+}
+
+fn notify_deaths(
+    mut died: EventReader<CharacterDied>,
+    labels: Query<&EntityLabel>,
+    transforms: Query<&GlobalTransform>,
+    mut notifications: EventWriter<Notification>,
+) {
+    for ev in died.read() {
+        let name = labels.get(ev.entity).map(|l| l.0.clone()).unwrap_or_else(|_| format!("{:?}", ev.entity));
+        let mut notification = Notification::critical("Needs", format!("{} {}", name, ev.cause.describe())).with_target(ev.entity);
+        // Positioned now, the body is gone by the time anyone clicks the entry
+        if let Ok(transform) = transforms.get(ev.entity) {
+            notification = notification.at(transform.translation().truncate());
+        }
+        notifications.write(notification);
+    }
+}
+
+// Warns once when a need turns critical, and again only after it has recovered
+fn warn_critical_needs(
+    config: Res<NeedsConfig>,
+    mut warned: Local<HashSet<(Entity, DeathCause)>>,
+    query: Query<(Entity, &EntityLabel, &Hunger, &Thirst, &Sleep)>,
+    mut notifications: EventWriter<Notification>,
+) {
+    let mut critical = HashSet::default();
+    for (entity, label, hunger, thirst, sleep) in &query {
+        for (value, cause) in [(hunger.value, DeathCause::Starvation), (thirst.value, DeathCause::Dehydration), (sleep.value, DeathCause::Exhaustion)] {
+            if value > config.critical_threshold { continue; }
+            critical.insert((entity, cause));
+            if !warned.contains(&(entity, cause)) {
+                notifications.write(Notification::warning("Needs", format!("{} {}", label.0, cause.warning())).with_target(entity));
+            }
+        }
+    }
+    *warned = critical;
+}
@@ -0,0 +1,409 @@
+pub struct NotificationSystems;
+use crate::*;
+use crate::camera::CameraFollow;
+use crate::clock::GameClock;
+use bevy::prelude::*;
+use bevy::input::mouse::{MouseScrollUnit, MouseWheel};
+use bevy::ui::RelativeCursorPosition;
+use std::collections::VecDeque;
+use std::fmt::Write as _;
+use std::path::Path;
+
+impl Plugin for NotificationSystems {
+    fn build(&self, app: &mut App) {
+        app
+            .insert_resource(NotificationConfig::default())
+            .init_resource::<NotificationLog>()
+            .init_resource::<NotificationHistory>()
+            .init_resource::<Toasts>()
+            .add_event::<Notification>()
+            .add_systems(Startup, spawn_toast_root)
+            // Last, so notifications posted anywhere in the frame, up to an exit, are still collected
+            .add_systems(Last, (collect_notifications, expire_toasts).chain())
+            .add_systems(Update, (toggle_history, rebuild_history, track_history_hover, scroll_history).chain())
+            .add_systems(Update, export_on_key)
+            .add_systems(Last, export_on_exit.after(collect_notifications));
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    Info,
+    Warning,
+    Critical,
+}
+
+impl Severity {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Severity::Info => "Info",
+            Severity::Warning => "Warning",
+            Severity::Critical => "Critical",
+        }
+    }
+
+    fn color(&self) -> Color {
+        match self {
+            Severity::Info => Color::srgb(0.9, 0.9, 0.9),
+            Severity::Warning => Color::srgb(1.0, 0.8, 0.3),
+            Severity::Critical => Color::srgb(1.0, 0.3, 0.3),
+        }
+    }
+}
+
+// Something the player should hear about. Written by any system; shown as a toast,
+// kept in the history and echoed to the console with `source` as the tag.
+#[derive(Event, Debug, Clone)]
+pub struct Notification {
+    pub severity: Severity,
+    pub source: &'static str,
+    pub message: String,
+    // Entity the camera jumps to from the history
+    pub target: Option<Entity>,
+    // Where the target was, for targets that may be despawned before the notification is read
+    pub position: Option<Vec2>,
+}
+
+impl Notification {
+    pub fn new(severity: Severity, source: &'static str, message: impl Into<String>) -> Self {
+        Self { severity, source, message: message.into(), target: None, position: None }
+    }
+
+    pub fn info(source: &'static str, message: impl Into<String>) -> Self {
+        Self::new(Severity::Info, source, message)
+    }
+
+    pub fn warning(source: &'static str, message: impl Into<String>) -> Self {
+        Self::new(Severity::Warning, source, message)
+    }
+
+    pub fn critical(source: &'static str, message: impl Into<String>) -> Self {
+        Self::new(Severity::Critical, source, message)
+    }
+
+    pub fn with_target(mut self, entity: Entity) -> Self {
+        self.target = Some(entity);
+        self
+    }
+
+    pub fn at(mut self, position: Vec2) -> Self {
+        self.position = Some(position);
+        self
+    }
+}
+
+#[derive(Resource, Debug, Clone, Copy)]
+pub struct NotificationConfig {
+    // Less severe notifications only go to the history
+    pub toast_severity: Severity,
+    pub toast_seconds: f32,
+    pub max_toasts: usize,
+    // Entries kept in the log and the report, older ones are dropped
+    pub capacity: usize,
+    // Newest entries listed in the history window
+    pub history_rows: usize,
+}
+
+impl Default for NotificationConfig {
+    fn default() -> Self {
+        Self {
+            toast_severity: Severity::Info,
+            toast_seconds: 8.0,
+            max_toasts: 6,
+            capacity: 5000,
+            history_rows: 100,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct LoggedNotification {
+    pub day: u32,
+    pub hour: u32,
+    pub minute: u32,
+    pub notification: Notification,
+}
+
+impl LoggedNotification {
+    fn time(&self) -> String {
+        format!("Day {} {:02}:{:02}", self.day, self.hour, self.minute)
+    }
+}
+
+// Every notification so far, oldest first
+#[derive(Resource, Debug, Default)]
+pub struct NotificationLog {
+    entries: VecDeque<LoggedNotification>,
+    // Bumped on every new entry so the history knows when to rebuild
+    revision: u64,
+}
+
+impl NotificationLog {
+    pub fn entries(&self) -> impl DoubleEndedIterator<Item = &LoggedNotification> {
+        self.entries.iter()
+    }
+
+    fn push(&mut self, entry: LoggedNotification, capacity: usize) {
+        self.entries.push_back(entry);
+        while self.entries.len() > capacity {
+            self.entries.pop_front();
+        }
+        self.revision += 1;
+    }
+
+    pub fn to_csv(&self) -> String {
+        let mut out = String::from("day,hour,minute,severity,source,message\n");
+        for entry in &self.entries {
+            let n = &entry.notification;
+            let _ = writeln!(out, "{},{},{},{},{},\"{}\"", entry.day, entry.hour, entry.minute, n.severity.name(), n.source, n.message.replace('"', "\"\""));
+        }
+        out
+    }
+
+    // Writes notifications.csv into `dir`, next to the metrics export
+    pub fn export(&self, dir: &Path) -> std::io::Result<()> {
+        std::fs::create_dir_all(dir)?;
+        std::fs::write(dir.join("notifications.csv"), self.to_csv())
+    }
+}
+
+#[derive(Component)]
+struct ToastRoot;
+
+#[derive(Component)]
+struct Toast {
+    expires: Timer,
+}
+
+// Toasts on screen, oldest first
+#[derive(Resource, Default)]
+struct Toasts(VecDeque<Entity>);
+
+fn spawn_toast_root(mut commands: Commands) {
+    commands.spawn((
+        Name::new("Toasts"),
+        Node {
+            position_type: PositionType::Absolute,
+            top: Val::Px(10.0),
+            left: Val::Px(10.0),
+            flex_direction: FlexDirection::Column,
+            row_gap: Val::Px(4.0),
+            ..default()
+        },
+        Pickable::IGNORE,
+        ToastRoot,
+    ));
+}
+
+fn collect_notifications(
+    mut commands: Commands,
+    config: Res<NotificationConfig>,
+    clock: Res<GameClock>,
+    mut log: ResMut<NotificationLog>,
+    mut toasts: ResMut<Toasts>,
+    mut notifications: EventReader<Notification>,
+    transforms: Query<&GlobalTransform>,
+    roots: Query<Entity, With<ToastRoot>>,
+) {
+    for notification in notifications.read() {
+        match notification.severity {
+            Severity::Info => info!("[{}] {}", notification.source, notification.message),
+            Severity::Warning => warn!("[{}] {}", notification.source, notification.message),
+            Severity::Critical => error!("[{}] {}", notification.source, notification.message),
+        }
+        let mut notification = notification.clone();
+        if notification.position.is_none() {
+            notification.position = notification.target.and_then(|e| transforms.get(e).ok()).map(|t| t.translation().truncate());
+        }
+
+        if notification.severity >= config.toast_severity {
+            if let Ok(root) = roots.single() {
+                let toast = commands.spawn((
+                    Text::new(notification.message.clone()),
+                    TextFont { font_size: 16.0, ..default() },
+                    TextColor(notification.severity.color()),
+                    Pickable::IGNORE,
+                    Toast { expires: Timer::from_seconds(config.toast_seconds, TimerMode::Once) },
+                )).id();
+                commands.entity(root).add_child(toast);
+                toasts.0.push_back(toast);
+                while toasts.0.len() > config.max_toasts {
+                    if let Some(oldest) = toasts.0.pop_front() {
+                        commands.entity(oldest).despawn();
+                    }
+                }
+            }
+        }
+
+        let entry = LoggedNotification { day: clock.day(), hour: clock.hour(), minute: clock.minute(), notification };
+        log.push(entry, config.capacity);
+    }
+}
+
+fn expire_toasts(
+    mut commands: Commands,
+    time: Res<Time<Real>>,
+    mut toasts: ResMut<Toasts>,
+    mut query: Query<(Entity, &mut Toast)>,
+) {
+    for (entity, mut toast) in &mut query {
+        if toast.expires.tick(time.delta()).finished() {
+            commands.entity(entity).despawn();
+            toasts.0.retain(|e| *e != entity);
+        }
+    }
+}
+
+#[derive(Resource, Default)]
+struct NotificationHistory {
+    open: bool,
+    hovered: bool,
+    // Log revision the rows were built from
+    shown_revision: Option<u64>,
+}
+
+#[derive(Component)]
+struct HistoryPanel;
+
+fn toggle_history(
+    mut commands: Commands,
+    actions: Res<Actions>,
+    mut history: ResMut<NotificationHistory>,
+    panels: Query<Entity, With<HistoryPanel>>,
+) {
+    if !actions.just_pressed(Action::ToggleNotifications) { return; }
+    history.open = !history.open;
+    if !history.open {
+        for panel in &panels {
+            commands.entity(panel).despawn();
+        }
+        return;
+    }
+    commands.spawn((
+        Name::new("Notification History"),
+        Node {
+            position_type: PositionType::Absolute,
+            right: Val::Px(10.0),
+            bottom: Val::Px(10.0),
+            width: Val::Px(460.0),
+            height: Val::Percent(40.0),
+            flex_direction: FlexDirection::Column,
+            row_gap: Val::Px(2.0),
+            padding: UiRect::all(Val::Px(6.0)),
+            overflow: Overflow::scroll_y(),
+            ..default()
+        },
+        BackgroundColor(Color::srgba(0.08, 0.08, 0.1, 0.9)),
+        RelativeCursorPosition::default(),
+        ScrollPosition::default(),
+        HistoryPanel,
+    ));
+    history.shown_revision = None;
+}
+
+// Newest first; entries with a target move the camera there when clicked
+fn rebuild_history(
+    mut commands: Commands,
+    config: Res<NotificationConfig>,
+    log: Res<NotificationLog>,
+    mut history: ResMut<NotificationHistory>,
+    panels: Query<Entity, With<HistoryPanel>>,
+) {
+    if !history.open || history.shown_revision == Some(log.revision) { return; }
+    let Ok(panel) = panels.single() else { return; };
+    history.shown_revision = Some(log.revision);
+
+    commands.entity(panel).despawn_related::<Children>().with_children(|list| {
+        if log.entries.is_empty() {
+            list.spawn((Text::new("Nothing has happened yet"), TextFont { font_size: 14.0, ..default() }));
+        }
+        for entry in log.entries().rev().take(config.history_rows) {
+            let n = &entry.notification;
+            let mut row = list.spawn((
+                Text::new(format!("{}  {}", entry.time(), n.message)),
+                TextFont { font_size: 14.0, ..default() },
+                TextColor(n.severity.color()),
+            ));
+            let (target, position) = (n.target, n.position);
+            if target.is_none() && position.is_none() { continue; }
+            row.observe(move |_: Trigger<Pointer<Click>>,
+                              targets: Query<&GlobalTransform>,
+                              mut follow: ResMut<CameraFollow>,
+                              mut cameras: Query<&mut Transform, With<MainCamera>>| {
+                // Prefer where the target is now, fall back to where it was
+                let current = target.and_then(|e| targets.get(e).ok()).map(|t| t.translation().truncate());
+                let Some(goal) = current.or(position) else { return; };
+                let Ok(mut camera) = cameras.single_mut() else { return; };
+                follow.0 = None;
+                camera.translation = goal.extend(camera.translation.z);
+            });
+        }
+    });
+}
+
+// Keeps world clicks and zooming away from the history while the cursor is over it
+fn track_history_hover(
+    mut history: ResMut<NotificationHistory>,
+    mut over_ui: ResMut<UiBlockHoverCount>,
+    panels: Query<&RelativeCursorPosition, With<HistoryPanel>>,
+) {
+    let hovered = panels.iter().any(|cursor| cursor.mouse_over());
+    if hovered == history.hovered { return; }
+    history.hovered = hovered;
+    if hovered {
+        over_ui.0 += 1;
+    } else if over_ui.0 > 0 {
+        over_ui.0 -= 1;
+    }
+}
+
+fn scroll_history(
+    history: Res<NotificationHistory>,
+    mut scroll: EventReader<MouseWheel>,
+    mut panels: Query<&mut ScrollPosition, With<HistoryPanel>>,
+) {
+    let lines: f32 = scroll
+        .read()
+        .map(|ev| match ev.unit {
+            MouseScrollUnit::Line => ev.y * 20.0,
+            MouseScrollUnit::Pixel => ev.y,
+        })
+        .sum();
+    if !history.hovered || lines == 0.0 { return; }
+    for mut position in &mut panels {
+        position.offset_y = (position.offset_y - lines).max(0.0);
+    }
+}
+
+fn export_notifications(log: &NotificationLog, options: &RunOptions) -> Option<Notification> {
+    log.export(&options.metrics_out)
+        .err()
+        .map(|err| Notification::warning("Notifications", format!("Export to {} failed: {}", options.metrics_out.display(), err)))
+}
+
+// Exported together with the metrics
+fn export_on_key(
+    actions: Res<Actions>,
+    options: Res<RunOptions>,
+    log: Res<NotificationLog>,
+    mut notifications: EventWriter<Notification>,
+) {
+    if actions.just_pressed(Action::ExportMetrics) {
+        if let Some(failure) = export_notifications(&log, &options) {
+            notifications.write(failure);
+        }
+    }
+}
+
+fn export_on_exit(
+    mut exits: EventReader<AppExit>,
+    options: Res<RunOptions>,
+    log: Res<NotificationLog>,
+) {
+    if exits.read().next().is_some() && (options.headless || options.export_on_exit) {
+        // The bus has been read for the last time, so a failure only reaches the console
+        if let Some(failure) = export_notifications(&log, &options) {
+            warn!("[{}] {}", failure.source, failure.message);
+        }
+    }
+}
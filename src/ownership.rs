@@ -0,0 +1,75 @@
+pub struct OwnershipSystems;
+use crate::*;
+use crate::production::{Bussiness, PlayerOwned};
+use bevy::prelude::*;
+
+impl Plugin for OwnershipSystems {
+    fn build(&self, app: &mut App) {
+        let world = app.world_mut();
+        let player = world.spawn((EntityLabel("Player".to_string()), Faction)).id();
+        let town = world.spawn((EntityLabel("World".to_string()), Faction)).id();
+        app
+            .insert_resource(Factions { player, world: town })
+            .init_resource::<ActiveBusiness>()
+            .add_systems(Update, (keep_active_business_valid, cycle_active_business).chain());
+    }
+}
+
+// A side in the game; owns businesses
+#[derive(Component)]
+pub struct Faction;
+
+#[derive(Resource, Debug, Clone, Copy)]
+pub struct Factions {
+    pub player: Entity,
+    // Town households, AI competitors and anything left without an owner
+    pub world: Entity,
+}
+
+// Owned entity -> owner. Buildings and workstations are owned by a business, businesses by a faction
+#[derive(Component, Debug)]
+#[relationship(relationship_target = Holdings)]
+pub struct Owner(pub Entity);
+
+#[derive(Component, Debug)]
+#[relationship_target(relationship = Owner)]
+pub struct Holdings(Vec<Entity>);
+
+impl Holdings {
+    pub fn count(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = Entity> + '_ {
+        self.0.iter().copied()
+    }
+}
+
+// Player business that newly placed buildings are assigned to
+#[derive(Resource, Debug, Default, Clone, Copy)]
+pub struct ActiveBusiness(pub Option<Entity>);
+
+fn keep_active_business_valid(
+    mut active: ResMut<ActiveBusiness>,
+    businesses: Query<Entity, (With<Bussiness>, With<PlayerOwned>)>,
+) {
+    if active.0.is_some_and(|b| businesses.contains(b)) { return; }
+    let first = businesses.iter().min();
+    if active.0 != first {
+        active.0 = first;
+    }
+}
+
+fn cycle_active_business(
+    actions: Res<Actions>,
+    mut active: ResMut<ActiveBusiness>,
+    businesses: Query<Entity, (With<Bussiness>, With<PlayerOwned>)>,
+) {
+    if !actions.just_pressed(Action::NextBusiness) { return; }
+    let mut all: Vec<Entity> = businesses.iter().collect();
+    all.sort();
+    let next = active.0
+        .and_then(|current| all.iter().position(|b| *b == current))
+        .map_or(0, |i| (i + 1) % all.len().max(1));
+    active.0 = all.get(next).copied();
+}
@@ -0,0 +1,367 @@
+pub struct PopulationSystems;
+use crate::*;
+use crate::building::{BuildingKind, BuildingPlaced};
+use crate::clock::DailySchedule;
+use crate::notifications::Notification;
+use crate::ownership::Owner;
+use crate::production::{Bussiness, EmployedBy, Employees, Workstation};
+use crate::zoning::ZoneRules;
+use bevy::prelude::*;
+use bevy::ecs::schedule::common_conditions::on_event;
+use std::collections::HashMap;
+
+impl Plugin for PopulationSystems {
+    fn build(&self, app: &mut App) {
+        app
+            .insert_resource(PopulationConfig::default())
+            .insert_resource(PopulationTicks(0))
+            .add_event::<CharacterEmigrated>()
+            .add_systems(Startup, setup_character_assets)
+            // Runs after Startup so `CommonMaterials` is available
+            .add_systems(PostStartup, spawn_initial_settlers)
+            .add_systems(Update, add_houses_on_placement)
+            .add_systems(
+                Update,
+                (assign_homes, assign_jobs, track_discontent, population_wave)
+                    .chain()
+                    .run_if(on_event::<WorldTick>),
+            )
+            .add_systems(PostUpdate, despawn_emigrants);
+    }
+}
+
+#[derive(Resource, Debug, Clone, Copy)]
+pub struct PopulationConfig {
+    pub initial_settlers: u32,
+    pub house_capacity: u32,
+    // Immigration and births are evaluated every this many ticks
+    pub wave_interval_ticks: u32,
+    pub max_immigrants_per_wave: u32,
+    // Any need below this value makes a character discontent
+    pub discontent_threshold: f32,
+    // Discontent ticks after which a character leaves the settlement
+    pub emigration_ticks: u32,
+    pub births_enabled: bool,
+    // Chance per wave that a house with at least two residents gets a new one
+    pub birth_chance: f64,
+}
+
+impl Default for PopulationConfig {
+    fn default() -> Self {
+        Self {
+            initial_settlers: 3,
+            house_capacity: 4,
+            wave_interval_ticks: 30,
+            max_immigrants_per_wave: 2,
+            discontent_threshold: 25.0,
+            emigration_ticks: 60,
+            births_enabled: false,
+            birth_chance: 0.05,
+        }
+    }
+}
+
+#[derive(Resource)]
+struct PopulationTicks(u32);
+
+#[derive(Resource)]
+pub struct CharacterAssets {
+    pub mesh: Handle<Mesh>,
+}
+
+#[derive(Component, Debug, Clone, Copy)]
+pub struct House {
+    pub capacity: u32,
+}
+
+// Character -> house it lives in
+#[derive(Component)]
+#[relationship(relationship_target = Residents)]
+pub struct Home(pub Entity);
+
+#[derive(Component)]
+#[relationship_target(relationship = Home)]
+pub struct Residents(Vec<Entity>);
+
+impl Residents {
+    pub fn count(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = Entity> + '_ {
+        self.0.iter().copied()
+    }
+}
+
+// Per-character multipliers rolled on spawn
+#[derive(Component, Debug, Clone, Copy)]
+pub struct Traits {
+    pub appetite: f32,
+    pub thirstiness: f32,
+    pub stamina: f32,
+}
+
+impl Traits {
+    pub fn random(rng: &mut impl Rng) -> Self {
+        Self {
+            appetite: rng.random_range(0.8..=1.2),
+            thirstiness: rng.random_range(0.8..=1.2),
+            stamina: rng.random_range(0.8..=1.2),
+        }
+    }
+}
+
+// Consecutive ticks with unmet needs, drained again while needs are met
+#[derive(Component, Default)]
+pub struct Discontent {
+    pub ticks: u32,
+}
+
+#[derive(Event, Debug, Clone, Copy)]
+pub struct CharacterEmigrated {
+    pub entity: Entity,
+}
+
+const NAME_STARTS: [&str; 12] = ["Vla", "Ma", "Ka", "Jo", "El", "Ri", "To", "An", "Mi", "Sa", "Be", "Lu"];
+const NAME_ENDS: [&str; 12] = ["d", "rek", "na", "han", "ia", "ko", "mas", "ton", "ra", "lin", "s", "ta"];
+const SURNAMES: [&str; 10] = ["Miller", "Baker", "Smith", "Fisher", "Cooper", "Mason", "Carter", "Weaver", "Tanner", "Hunt"];
+
+pub fn generate_name(rng: &mut impl Rng) -> String {
+    let start = NAME_STARTS[rng.random_range(0..NAME_STARTS.len())];
+    let end = NAME_ENDS[rng.random_range(0..NAME_ENDS.len())];
+    let surname = SURNAMES[rng.random_range(0..SURNAMES.len())];
+    format!("{start}{end} {surname}")
+}
+
+pub fn spawn_character(
+    commands: &mut Commands,
+    assets: &CharacterAssets,
+    common_materials: &CommonMaterials,
+    name: String,
+    pos: Vec2,
+    rng: &mut impl Rng,
+) -> Entity {
+    let traits = Traits::random(rng);
+    let character = CharacterBundle {
+        name: EntityLabel(name),
+        health: Health(100.0),
+        hunger: Hunger::new(100.0).with_rate_scale(traits.appetite),
+        thirst: Thirst::new(100.0).with_rate_scale(traits.thirstiness),
+        sleep: Sleep::new(100.0).with_rate_scale(1.0 / traits.stamina),
+        activity: Activity::Idle,
+        speed: Speed(rng.random_range(40.0..=60.0)),
+        velocity: Velocity(Vec2::ZERO),
+        destination: Destination(pos),
+        tracked: TrackedByKDTree,
+    };
+    let visuals = VisualBundle {
+        mesh: Mesh2d(assets.mesh.clone()),
+        material: MeshMaterial2d(common_materials.hero.clone()),
+        transform: Transform::from_xyz(pos.x, pos.y, 0.0),
+    };
+    let collision = CollisionBundle::circle_sensor(
+        5.0, RigidBody::KinematicPositionBased, true);
+    let schedule = DailySchedule::worker(rng.random_range(6..=10), 9);
+    commands.spawn((character, visuals, collision, traits, schedule, Discontent::default())).id()
+}
+
+fn setup_character_assets(mut commands: Commands, mut meshes: ResMut<Assets<Mesh>>) {
+    commands.insert_resource(CharacterAssets { mesh: meshes.add(Mesh::from(Circle::new(5.0))) });
+}
+
+fn spawn_initial_settlers(
+    mut commands: Commands,
+    config: Res<PopulationConfig>,
+    assets: Res<CharacterAssets>,
+    common_materials: Res<CommonMaterials>,
+) {
+    let mut rng = rand::rng();
+    for _ in 0..config.initial_settlers {
+        let pos = Vec2::new(rng.random_range(-400.0..=400.0), rng.random_range(-400.0..=400.0));
+        let name = generate_name(&mut rng);
+        spawn_character(&mut commands, &assets, &common_materials, name, pos, &mut rng);
+    }
+}
+
+fn add_houses_on_placement(
+    mut commands: Commands,
+    mut placed: EventReader<BuildingPlaced>,
+    config: Res<PopulationConfig>,
+) {
+    for ev in placed.read() {
+        if ev.kind == BuildingKind::House {
+            commands.entity(ev.entity).insert((House { capacity: config.house_capacity }, Bed));
+        }
+    }
+}
+
+fn free_capacity(house: &House, residents: Option<&Residents>) -> u32 {
+    house.capacity.saturating_sub(residents.map_or(0, |r| r.count()) as u32)
+}
+
+// Open worker slots per business, summed over its workstations
+fn open_jobs(
+    workstations: &Query<(&Workstation, &Owner)>,
+    businesses: &Query<(Entity, Option<&Employees>), With<Bussiness>>,
+) -> HashMap<Entity, u32> {
+    let mut slots: HashMap<Entity, u32> = HashMap::new();
+    for (workstation, business) in workstations {
+        *slots.entry(business.0).or_default() += workstation.worker_slots;
+    }
+    businesses
+        .iter()
+        .map(|(entity, employees)| {
+            let total = slots.get(&entity).copied().unwrap_or(0);
+            (entity, total.saturating_sub(employees.map_or(0, |e| e.count()) as u32))
+        })
+        .collect()
+}
+
+// Orders houses by how well their zone suits living, dropping those whose zone no longer allows houses
+fn by_zone<T>(houses: &mut Vec<T>, pos: impl Fn(&T) -> Vec2, grid: &WorldGrid, rules: &ZoneRules) {
+    houses.retain(|h| rules.housing_rank(grid.zone(grid.world_to_grid(pos(h)))).is_some());
+    houses.sort_by_key(|h| rules.housing_rank(grid.zone(grid.world_to_grid(pos(h)))));
+}
+
+fn assign_homes(
+    mut commands: Commands,
+    grid: Res<WorldGrid>,
+    zone_rules: Res<ZoneRules>,
+    homeless: Query<Entity, (With<Health>, Without<Home>)>,
+    houses: Query<(Entity, &House, Option<&Residents>, &Transform)>,
+    mut destinations: Query<&mut Destination>,
+) {
+    let mut free: Vec<(Entity, Vec2, u32)> = houses
+        .iter()
+        .map(|(e, house, residents, t)| (e, t.translation.truncate(), free_capacity(house, residents)))
+        .filter(|(_, _, free)| *free > 0)
+        .collect();
+    by_zone(&mut free, |h| h.1, &grid, &zone_rules);
+
+    for character in &homeless {
+        let Some(slot) = free.iter_mut().find(|(_, _, free)| *free > 0) else { break; };
+        slot.2 -= 1;
+        commands.entity(character).insert(Home(slot.0));
+        if let Ok(mut dest) = destinations.get_mut(character) {
+            dest.0 = slot.1;
+        }
+    }
+}
+
+fn assign_jobs(
+    mut commands: Commands,
+    unemployed: Query<Entity, (With<Health>, Without<EmployedBy>)>,
+    workstations: Query<(&Workstation, &Owner)>,
+    businesses: Query<(Entity, Option<&Employees>), With<Bussiness>>,
+) {
+    let mut open: Vec<(Entity, u32)> = open_jobs(&workstations, &businesses).into_iter().collect();
+    for character in &unemployed {
+        let Some(slot) = open.iter_mut().find(|(_, free)| *free > 0) else { break; };
+        slot.1 -= 1;
+        commands.entity(character).insert(EmployedBy(slot.0));
+    }
+}
+
+fn track_discontent(
+    config: Res<PopulationConfig>,
+    mut emigrated: EventWriter<CharacterEmigrated>,
+    mut query: Query<(Entity, &mut Discontent, &Hunger, &Thirst, &Sleep, Option<&EntityLabel>)>,
+    mut notifications: EventWriter<Notification>,
+) {
+    for (entity, mut discontent, hunger, thirst, sleep, label) in &mut query {
+        let unmet = [hunger.value, thirst.value, sleep.value]
+            .iter()
+            .any(|v| *v < config.discontent_threshold);
+        if unmet {
+            discontent.ticks += 1;
+        } else {
+            discontent.ticks = discontent.ticks.saturating_sub(1);
+        }
+        if discontent.ticks == config.emigration_ticks {
+            emigrated.write(CharacterEmigrated { entity });
+            let name = label.map_or_else(|| format!("{:?}", entity), |l| l.0.clone());
+            notifications.write(Notification::warning("Population", format!("{} is leaving town over unmet needs", name)).with_target(entity));
+        }
+    }
+}
+
+fn population_wave(
+    mut commands: Commands,
+    mut ticks: ResMut<PopulationTicks>,
+    config: Res<PopulationConfig>,
+    assets: Res<CharacterAssets>,
+    common_materials: Res<CommonMaterials>,
+    grid: Res<WorldGrid>,
+    zone_rules: Res<ZoneRules>,
+    houses: Query<(Entity, &House, Option<&Residents>, &Transform)>,
+    workstations: Query<(&Workstation, &Owner)>,
+    businesses: Query<(Entity, Option<&Employees>), With<Bussiness>>,
+    mut notifications: EventWriter<Notification>,
+) {
+    ticks.0 += 1;
+    if ticks.0 % config.wave_interval_ticks.max(1) != 0 { return; }
+
+    let mut rng = rand::rng();
+    let mut free_houses: Vec<(Entity, Vec2, u32, usize)> = houses
+        .iter()
+        .map(|(e, house, residents, t)| {
+            (e, t.translation.truncate(), free_capacity(house, residents), residents.map_or(0, |r| r.count()))
+        })
+        .filter(|(_, _, free, _)| *free > 0)
+        .collect();
+
+    if config.births_enabled {
+        for (house, pos, free, residents) in free_houses.iter_mut() {
+            if *residents >= 2 && *free > 0 && rng.random_bool(config.birth_chance) {
+                let name = generate_name(&mut rng);
+                let announcement = format!("{} was born", name);
+                let child = spawn_character(&mut commands, &assets, &common_materials, name, *pos, &mut rng);
+                commands.entity(child).insert(Home(*house));
+                notifications.write(Notification::info("Population", announcement).with_target(child));
+                *free -= 1;
+            }
+        }
+    }
+
+    // Immigrants only move into houses their zone allows, residential ones first
+    by_zone(&mut free_houses, |h| h.1, &grid, &zone_rules);
+
+    // Immigrants only come when there is both a free bed and a free job
+    let free_beds: u32 = free_houses.iter().map(|(_, _, free, _)| *free).sum();
+    let free_jobs: u32 = open_jobs(&workstations, &businesses).values().sum();
+    let arrivals = config.max_immigrants_per_wave.min(free_beds).min(free_jobs);
+
+    let half_w = (grid.width() * grid.scale()) as f32 / 2.0;
+    let half_h = (grid.height() * grid.scale()) as f32 / 2.0;
+    let mut arrived = 0;
+    for _ in 0..arrivals {
+        let Some(slot) = free_houses.iter_mut().find(|(_, _, free, _)| *free > 0) else { break; };
+        arrived += 1;
+        slot.2 -= 1;
+        // Arrive from a random point on the map edge and walk home
+        let edge = if rng.random_bool(0.5) {
+            Vec2::new(if rng.random_bool(0.5) { -half_w } else { half_w }, rng.random_range(-half_h..=half_h))
+        } else {
+            Vec2::new(rng.random_range(-half_w..=half_w), if rng.random_bool(0.5) { -half_h } else { half_h })
+        };
+        let name = generate_name(&mut rng);
+        let immigrant = spawn_character(&mut commands, &assets, &common_materials, name, edge, &mut rng);
+        commands.entity(immigrant).insert((Home(slot.0), Destination(slot.1)));
+    }
+    match arrived {
+        0 => {}
+        1 => { notifications.write(Notification::info("Population", "A newcomer is moving in")); }
+        n => { notifications.write(Notification::info("Population", format!("{} newcomers are moving in", n))); }
+    }
+}
+
+fn despawn_emigrants(
+    mut commands: Commands,
+    mut emigrated: EventReader<CharacterEmigrated>,
+) {
+    for ev in emigrated.read() {
+        if let Ok(mut entity) = commands.get_entity(ev.entity) {
+            entity.despawn();
+        }
+    }
+}
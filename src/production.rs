@@ -2,33 +2,285 @@ pub struct ProductionSystems;
 use crate::*;
 use bevy::prelude::*;
 use bevy::ecs::schedule::common_conditions::on_event;
+use crate::clock::{GameClock, OpeningHours};
+use crate::market::{MarketOrders, TradePolicy};
+use crate::finance::{Solvency, SolvencyStage};
+use crate::logistics::DropOff;
+use crate::notifications::Notification;
+use crate::jobs::Workers;
+use crate::skills::{Skill, SkillConfig, Skills};
+use crate::ownership::{ActiveBusiness, Factions, Owner};
+use crate::storage::{spill_to_stockpile, GroundStockpile, OutputBlocked, Overflow, OverflowPolicy, StorageCapacity, StorageConfig};
+use crate::building::{spawn_building, BuildingKind, BuildingPlaced, ConstructionSite, Footprint};
+use std::collections::{HashMap, VecDeque};
 
 impl Plugin for ProductionSystems {
     fn build(&self, app: &mut App) {
         app
-            .add_systems(Startup, test_setup_production)
-            .add_systems(Update, produce_resource.run_if(on_event::<WorldTick>))
+            .add_event::<GoodsProduced>()
+            // Needs `CommonMaterials` for the starting workshops
+            .add_systems(PostStartup, test_setup_production)
+            .add_systems(Update, (update_operational, produce_resource).chain().run_if(on_event::<WorldTick>))
+            .add_systems(Update, announce_first_output)
             .add_systems(Update, show_business_ui);
     }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, serde::Deserialize)]
+pub(crate) enum Good {
+    Food,
+    Grain,
+    Wood,
+}
+
+impl Good {
+    pub(crate) const ALL: [Good; 3] = [Good::Food, Good::Grain, Good::Wood];
+
+    pub(crate) fn name(&self) -> &'static str {
+        match self {
+            Good::Food => "Food",
+            Good::Grain => "Grain",
+            Good::Wood => "Wood",
+        }
+    }
+}
+
+// A lot of a perishable good that entered storage together, with `freshness` ticks of shelf life left
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Batch {
+    pub(crate) amount: i32,
+    pub(crate) freshness: f32,
+}
+
+impl Batch {
+    pub(crate) fn fresh(good: Good, amount: i32) -> Self {
+        Self { amount, freshness: good.shelf_life().unwrap_or(f32::INFINITY) }
+    }
+
+    // Splits off the oldest `amount` units; returns (taken, rest)
+    pub(crate) fn split(batches: Vec<Batch>, amount: i32) -> (Vec<Batch>, Vec<Batch>) {
+        let (mut taken, mut rest) = (Vec::new(), Vec::new());
+        let mut left = amount.max(0);
+        for batch in batches {
+            let n = batch.amount.min(left);
+            left -= n;
+            if n > 0 { taken.push(Batch { amount: n, ..batch }); }
+            if batch.amount > n { rest.push(Batch { amount: batch.amount - n, ..batch }); }
+        }
+        (taken, rest)
+    }
+
+    pub(crate) fn total(batches: &[Batch]) -> i32 {
+        batches.iter().map(|b| b.amount).sum()
+    }
+}
+
 #[derive(Component, Default)]
-struct Storage {
-    food: i32,
-    money: i32
+pub(crate) struct Storage {
+    pub(crate) goods: HashMap<Good, i32>,
+    pub(crate) money: i32,
+    // Perishable stock by batch, oldest first; adds up to the counts in `goods`
+    pub(crate) batches: HashMap<Good, VecDeque<Batch>>,
+}
+
+impl Storage {
+    pub(crate) fn with_money(money: i32) -> Self {
+        Self { money, ..default() }
+    }
+
+    pub(crate) fn amount(&self, good: Good) -> i32 {
+        self.goods.get(&good).copied().unwrap_or(0)
+    }
+
+    // Adds freshly made goods
+    pub(crate) fn add(&mut self, good: Good, amount: i32) {
+        self.add_batches(good, [Batch::fresh(good, amount)]);
+    }
+
+    // Adds goods that keep their age, e.g. when hauled or traded
+    pub(crate) fn add_batches(&mut self, good: Good, batches: impl IntoIterator<Item = Batch>) {
+        for batch in batches {
+            if batch.amount <= 0 { continue; }
+            *self.goods.entry(good).or_default() += batch.amount;
+            if good.shelf_life().is_none() { continue; }
+            let queue = self.batches.entry(good).or_default();
+            let at = queue.iter().position(|b| b.freshness > batch.freshness).unwrap_or(queue.len());
+            match at.checked_sub(1).and_then(|i| queue.get_mut(i)) {
+                Some(previous) if previous.freshness == batch.freshness => previous.amount += batch.amount,
+                _ => queue.insert(at, batch),
+            }
+        }
+    }
+
+    // Removes `amount` only if all of it is in stock, oldest batches first
+    pub(crate) fn take(&mut self, good: Good, amount: i32) -> bool {
+        if self.amount(good) < amount { return false; }
+        self.take_batches(good, amount);
+        true
+    }
+
+    // Removes up to `amount`, oldest batches first, and hands them over
+    pub(crate) fn take_batches(&mut self, good: Good, amount: i32) -> Vec<Batch> {
+        let stock = self.goods.entry(good).or_default();
+        let amount = amount.min(*stock).max(0);
+        *stock -= amount;
+        let Some(queue) = self.batches.get_mut(&good) else {
+            return vec![Batch::fresh(good, amount)];
+        };
+        let mut taken = Vec::new();
+        let mut left = amount;
+        while left > 0 {
+            let Some(oldest) = queue.front_mut() else { break; };
+            let n = oldest.amount.min(left);
+            taken.push(Batch { amount: n, freshness: oldest.freshness });
+            oldest.amount -= n;
+            left -= n;
+            if oldest.amount == 0 { queue.pop_front(); }
+        }
+        taken
+    }
+
+    pub(crate) fn clear_goods(&mut self) {
+        self.goods.clear();
+        self.batches.clear();
+    }
 }
 
 #[derive(Component)]
-struct Workstation {
+pub(crate) struct Workstation {
     current_work: f32,
     total_work: f32,
+    pub(crate) output: Good,
+    // Goods consumed from the business storage per item produced
+    pub(crate) inputs: Vec<(Good, i32)>,
+    // How many characters the business can employ for this workstation
+    pub(crate) worker_slots: u32,
+}
+
+impl Workstation {
+    pub(crate) fn new(total_work: f32, output: Good, inputs: Vec<(Good, i32)>, worker_slots: u32) -> Self {
+        Self { current_work: 0.0, total_work, output, inputs, worker_slots }
+    }
+}
+
+// Finished items leaving a workstation
+#[derive(Event, Debug, Clone, Copy)]
+pub(crate) struct GoodsProduced {
+    pub(crate) workstation: Entity,
+    pub(crate) business: Entity,
+    pub(crate) good: Good,
+    pub(crate) amount: i32,
+}
+
+// Tells the player the first time a business turns out each good
+fn announce_first_output(
+    mut produced: EventReader<GoodsProduced>,
+    mut announced: Local<HashSet<(Entity, Good)>>,
+    labels: Query<&EntityLabel>,
+    mut notifications: EventWriter<Notification>,
+) {
+    for ev in produced.read() {
+        if !announced.insert((ev.business, ev.good)) { continue; }
+        let name = labels.get(ev.business).map_or_else(|_| format!("{:?}", ev.business), |l| l.0.clone());
+        notifications.write(Notification::info("Production", format!("{} produced its first {}", name, ev.good.name())).with_target(ev.workstation));
+    }
 }
 
+#[derive(Debug, Clone)]
+pub(crate) struct Recipe {
+    pub(crate) output: Good,
+    pub(crate) total_work: f32,
+    pub(crate) inputs: Vec<(Good, i32)>,
+    pub(crate) worker_slots: u32,
+}
+
+// A workstation that comes with a building kind, at `cell` counted from the footprint's bottom-left
+#[derive(Debug, Clone)]
+pub(crate) struct StationSpec {
+    pub(crate) recipe: Recipe,
+    pub(crate) cell: Vec2,
+}
+
+impl BuildingKind {
+    pub(crate) fn stations(&self) -> Vec<StationSpec> {
+        match self {
+            BuildingKind::House | BuildingKind::Cellar | BuildingKind::ColdStore => Vec::new(),
+            BuildingKind::Workshop => vec![
+                StationSpec { recipe: Recipe { output: Good::Food, total_work: 50.0, inputs: Vec::new(), worker_slots: 3 }, cell: Vec2::new(1.0, 2.0) },
+                StationSpec { recipe: Recipe { output: Good::Food, total_work: 25.0, inputs: Vec::new(), worker_slots: 2 }, cell: Vec2::new(3.0, 2.0) },
+            ],
+        }
+    }
+}
+
+// Where operators stand, relative to their workstation; one spot per worker slot
+#[derive(Component, Debug, Clone)]
+pub(crate) struct WorkerSpots(pub(crate) Vec<Vec2>);
+
+// A building whose workstations may run: construction finished and its owner has somewhere to deliver output
+#[derive(Component)]
+pub(crate) struct Operational;
+
+// Spawns the catalog workstations of `kind` as children of `building`, owned by `owner`.
+// `size` is the placed footprint; when its sides are swapped the station layout turns a quarter with it.
+// `recipe` replaces the catalog recipe of every station, e.g. for AI owners choosing what to make.
+pub(crate) fn spawn_workstations(
+    commands: &mut Commands,
+    grid: &WorldGrid,
+    building: Entity,
+    owner: Entity,
+    kind: BuildingKind,
+    size: Vec2,
+    recipe: Option<&Recipe>,
+) {
+    let scale = grid.scale() as f32;
+    let rotated = size != kind.size();
+    // Quarter turn counter-clockwise about the footprint's centre
+    let turn = |v: Vec2| if rotated { Vec2::new(-v.y, v.x) } else { v };
+    for spec in kind.stations() {
+        let recipe = recipe.unwrap_or(&spec.recipe);
+        let local = turn(spec.cell + 0.5 - kind.size() / 2.0) * scale;
+        let slots = recipe.worker_slots;
+        let spots = (0..slots)
+            .map(|i| turn(Vec2::new((i as f32 - (slots as f32 - 1.0) / 2.0) * scale * 0.6, -scale * 0.6)))
+            .collect();
+        let workstation = commands.spawn((
+            EntityLabel(format!("{} bench", recipe.output.name())),
+            Workstation::new(recipe.total_work, recipe.output, recipe.inputs.clone(), slots),
+            // Output waits here until haulers bring it to a drop-off
+            Storage::default(),
+            WorkerSpots(spots),
+            Transform::from_xyz(local.x, local.y, 0.1),
+            Owner(owner),
+        )).id();
+        commands.entity(building).add_child(workstation);
+    }
+}
+
+#[derive(Component)]
+pub(crate) struct Bussiness;
+
 #[derive(Component)]
-struct Bussiness;
+pub(crate) struct PlayerOwned;
 
+// Character -> business it works for
 #[derive(Component)]
-struct PlayerOwned;
+#[relationship(relationship_target = Employees)]
+pub(crate) struct EmployedBy(pub(crate) Entity);
+
+#[derive(Component)]
+#[relationship_target(relationship = EmployedBy)]
+pub(crate) struct Employees(Vec<Entity>);
+
+impl Employees {
+    pub(crate) fn count(&self) -> usize {
+        self.0.len()
+    }
+
+    pub(crate) fn iter(&self) -> impl Iterator<Item = Entity> + '_ {
+        self.0.iter().copied()
+    }
+}
 
 // === UI components for Business HUD ===
 #[derive(Component)]
@@ -40,34 +292,156 @@ struct BusinessUiEntry {
 }
 
 fn test_setup_production(
-    mut commands : Commands
+    mut commands : Commands,
+    factions: Res<Factions>,
+    mut grid: ResMut<WorldGrid>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    common_materials: Res<CommonMaterials>,
+    mut placed: EventWriter<BuildingPlaced>,
 ){
-    let business_id = commands.spawn((EntityLabel("Bussiness".to_string()), Bussiness, PlayerOwned, Storage {food:0, money:0})).id();
-    let ws1 = commands.spawn((EntityLabel("Work50".to_string()), Workstation { current_work: 0.0, total_work: 50.0})).id();
-    let ws2 = commands.spawn((EntityLabel("Work25".to_string()), Workstation { current_work: 0.0, total_work: 25.0})).id();
-    commands.entity(business_id).add_children(&[ws1, ws2]);
-    let business_id = commands.spawn((EntityLabel("Bussiness2".to_string()), Bussiness, PlayerOwned, Storage {food:0, money:0})).id();
-    let ws1 = commands.spawn((EntityLabel("Work50".to_string()), Workstation { current_work: 0.0, total_work: 50.0})).id();
-    let ws2 = commands.spawn((EntityLabel("Work25".to_string()), Workstation { current_work: 0.0, total_work: 25.0})).id();
-    commands.entity(business_id).add_children(&[ws1, ws2]);
+    let sell_surplus_food = || (MarketOrders::default(), TradePolicy {
+        sell_above: HashMap::from([(Good::Food, 5)]),
+        sell_margin: 0.8,
+        buy_margin: 1.2,
+        ..default()
+    });
+    // A finished workshop per business, already standing when the game starts
+    let mut place_workshop = |commands: &mut Commands, business: Entity, origin: Vec2| {
+        let kind = BuildingKind::Workshop;
+        let size = kind.size();
+        let pos = grid.grid_to_world(origin, size);
+        let building = spawn_building(commands, &mut meshes, &grid, common_materials.building.clone(), size, pos);
+        commands.entity(building).insert((kind, Footprint { origin, size }, Owner(business)));
+        grid.modify_rectangle(origin, size);
+        spawn_workstations(commands, &grid, building, business, kind, size, None);
+        placed.write(BuildingPlaced { entity: building, kind, origin, size });
+    };
+    let business_id = commands.spawn((EntityLabel("Bussiness".to_string()), Bussiness, PlayerOwned, Owner(factions.player), Storage::with_money(1000), OpeningHours { open: 8, close: 18 }, sell_surplus_food())).id();
+    place_workshop(&mut commands, business_id, Vec2::new(73.0, 84.0));
+    commands.spawn((EntityLabel("Warehouse".to_string()), DropOff { storage: business_id }, Transform::from_xyz(-175.0, 200.0, 0.0)));
+    let business_id = commands.spawn((EntityLabel("Bussiness2".to_string()), Bussiness, PlayerOwned, Owner(factions.player), Storage::with_money(1000), sell_surplus_food())).id();
+    place_workshop(&mut commands, business_id, Vec2::new(87.0, 84.0));
+    commands.spawn((EntityLabel("Warehouse2".to_string()), DropOff { storage: business_id }, Transform::from_xyz(175.0, 200.0, 0.0)));
+}
+
+fn update_operational(
+    mut commands: Commands,
+    buildings: Query<(Entity, Option<&Owner>, Has<ConstructionSite>, Has<Operational>), With<Building>>,
+    drop_offs: Query<&DropOff>,
+) {
+    let delivering: Vec<Entity> = drop_offs.iter().map(|d| d.storage).collect();
+    for (building, owner, under_construction, operational) in &buildings {
+        let connected = owner.is_some_and(|o| delivering.contains(&o.0));
+        let should_run = connected && !under_construction;
+        if should_run && !operational {
+            commands.entity(building).insert(Operational);
+        } else if !should_run && operational {
+            commands.entity(building).remove::<Operational>();
+        }
+    }
 }
 
 fn produce_resource(
-    mut workstation_query: Query<(&mut Workstation, &ChildOf)>,
+    mut workstation_query: Query<(Entity, &mut Workstation, &Owner, &ChildOf, Option<&Workers>)>,
+    operational_query: Query<(), With<Operational>>,
     mut storages_query: Query<&mut Storage>,
+    hours_query: Query<&OpeningHours>,
+    solvency_query: Query<&Solvency>,
+    skills_query: Query<&Skills>,
+    skill_config: Res<SkillConfig>,
+    clock: Res<GameClock>,
+    mut produced_events: EventWriter<GoodsProduced>,
+    mut commands: Commands,
+    storage_config: Res<StorageConfig>,
+    capacity_query: Query<&StorageCapacity>,
+    overflow_query: Query<&Overflow>,
+    blocked_query: Query<(), With<OutputBlocked>>,
+    positions_query: Query<&GlobalTransform>,
+    stockpiles_query: Query<(Entity, &Owner, &GlobalTransform), With<GroundStockpile>>,
 ) {
     let given_produce_per_worker = 2.0;
-    let workers = 5.0;
     let product_to_produce = 1.0;
+    let mut rng = rand::rng();
 
-    for (mut workstation, business) in &mut workstation_query {
-        let new_current_work = workstation.current_work + (given_produce_per_worker * workers);
+    for (ws_entity, mut workstation, business, building, workers) in &mut workstation_query {
+        if !operational_query.contains(building.parent()) {
+            continue;
+        }
+        // Only agents currently on an operate shift here count, each sped up by their skill
+        let skill = Skill::for_good(workstation.output);
+        // (level, speed multiplier) of each worker
+        let crew: Vec<(f32, f32)> = workers
+            .map(|w| w.iter().map(|worker| {
+                skills_query.get(worker).map_or((0.0, 1.0), |s| (s.level(skill), s.speed_multiplier(skill, &skill_config)))
+            }).collect())
+            .unwrap_or_default();
+        if crew.is_empty() {
+            continue;
+        }
+        let work_done: f32 = crew.iter().map(|(_, speed)| given_produce_per_worker * speed).sum();
+        let average_level = crew.iter().map(|(level, _)| level).sum::<f32>() / crew.len() as f32;
+        if hours_query.get(business.0).is_ok_and(|h| !h.is_open(clock.hour())) {
+            continue;
+        }
+        if solvency_query.get(business.0).is_ok_and(|s| s.stage == SolvencyStage::ShutDown) {
+            continue;
+        }
+        let new_current_work = workstation.current_work + work_done;
         if new_current_work >= workstation.total_work {
-            let items_produced = (new_current_work / workstation.total_work).floor();
-            workstation.current_work = new_current_work - (workstation.total_work * items_produced);
-            if let Ok(mut storage) = storages_query.get_mut(business.parent()) {
-                storage.food += (product_to_produce * items_produced) as i32;
+            // Output goes to the workstation's own buffer if it has one, otherwise straight to the business
+            let output_to = if storages_query.contains(ws_entity) { ws_entity } else { business.0 };
+            let room = match (capacity_query.get(output_to), storages_query.get(output_to)) {
+                (Ok(capacity), Ok(storage)) => capacity.room_for(&storage, workstation.output),
+                _ => i32::MAX,
+            };
+            let policy = overflow_query.get(ws_entity).map_or(OverflowPolicy::default(), |o| o.0);
+
+            let Ok(mut storage) = storages_query.get_mut(business.0) else { continue; };
+            let items_finished = (new_current_work / workstation.total_work).floor() as i32;
+            // Only finish as many items as there are inputs for; the rest waits at full progress
+            let mut items_produced = workstation.inputs
+                .iter()
+                .map(|(good, amount)| storage.amount(*good) / (*amount).max(1))
+                .fold(items_finished, i32::min);
+            // A halting station also waits for room in its output storage
+            if policy == OverflowPolicy::Halt {
+                items_produced = items_produced.min(room);
+            }
+            for (good, amount) in &workstation.inputs {
+                storage.take(*good, amount * items_produced);
+            }
+            // Skilled crews sometimes get an extra item out of the same work
+            let bonus_chance = (average_level * skill_config.quality_per_level).clamp(0.0, 1.0) as f64;
+            let mut bonus = (0..items_produced).filter(|_| rng.random_bool(bonus_chance)).count() as i32;
+            let base = (product_to_produce * items_produced as f32) as i32;
+            // A halting station only gets the bonus items it has room for
+            if policy == OverflowPolicy::Halt {
+                bonus = bonus.min(room.saturating_sub(base)).max(0);
             }
+            let produced = base + bonus;
+            let kept = produced.min(room);
+            if let Ok(mut output) = storages_query.get_mut(output_to) {
+                output.add(workstation.output, kept);
+            }
+            // Spilling stations drop what does not fit on the ground
+            if policy == OverflowPolicy::Spill && produced > kept {
+                let pos = positions_query.get(ws_entity).map_or(Vec2::ZERO, |t| t.translation().truncate());
+                spill_to_stockpile(&mut commands, &storage_config, &stockpiles_query, &mut storages_query, business.0, pos, workstation.output, vec![Batch::fresh(workstation.output, produced - kept)]);
+            }
+            if produced > 0 {
+                produced_events.write(GoodsProduced { workstation: ws_entity, business: business.0, good: workstation.output, amount: produced });
+            }
+            // Spilling stations never stop, so only halting ones can be blocked
+            let blocked = policy == OverflowPolicy::Halt && room < items_finished;
+            if blocked != blocked_query.contains(ws_entity) {
+                if blocked {
+                    commands.entity(ws_entity).insert(OutputBlocked);
+                } else {
+                    commands.entity(ws_entity).remove::<OutputBlocked>();
+                }
+            }
+            workstation.current_work = (new_current_work - workstation.total_work * items_produced as f32)
+                .min(workstation.total_work);
         } else {
             workstation.current_work = new_current_work;
         }
@@ -80,6 +454,8 @@ fn show_business_ui(
     root_query: Query<Entity, With<BusinessUiRoot>>,
     entry_query: Query<(Entity, &BusinessUiEntry)>,
     mut text_query: Query<&mut Text>,
+    active: Res<ActiveBusiness>,
+    blocked_query: Query<&Owner, With<OutputBlocked>>,
 ) {
     // Ensure there is a UI root anchored to the top-right
     let root_entity = match root_query.single() {
@@ -104,7 +480,6 @@ fn show_business_ui(
     };
 
     // Build a lookup of existing UI entries by target entity
-    use std::collections::HashMap;
     let mut existing_entries: HashMap<Entity, Entity> = HashMap::new();
     for (entry_entity, entry) in &entry_query {
         existing_entries.insert(entry.target, entry_entity);
@@ -117,7 +492,13 @@ fn show_business_ui(
     for (biz_entity, label_opt, storage) in &player_businesses {
         seen_targets.push(biz_entity);
         let name = label_opt.map(|l| l.0.clone()).unwrap_or_else(|| format!("Business {:?}", biz_entity));
-        let line = format!("{}  |  Food: {}   Money: {}", name, storage.food, storage.money);
+        // New buildings go to the active business, switched with Tab
+        let marker = if active.0 == Some(biz_entity) { "> " } else { "" };
+        let mut line = format!("{}{}  |  Food: {}   Money: {}", marker, name, storage.amount(Good::Food), storage.money);
+        let blocked = blocked_query.iter().filter(|o| o.0 == biz_entity).count();
+        if blocked > 0 {
+            line.push_str(&format!("   Output blocked: {}", blocked));
+        }
 
         if let Some(entry_entity) = existing_entries.get(&biz_entity).copied() {
             // Update existing text
@@ -0,0 +1,135 @@
+pub struct SkillSystems;
+use crate::*;
+use crate::jobs::{CurrentJob, JobBoard};
+use crate::production::Good;
+use bevy::prelude::*;
+use bevy::ecs::schedule::common_conditions::on_event;
+use std::collections::HashMap;
+
+impl Plugin for SkillSystems {
+    fn build(&self, app: &mut App) {
+        app
+            .insert_resource(SkillConfig::default())
+            .add_systems(Update, roll_starting_skills)
+            .add_systems(Update, practice_skills.run_if(on_event::<WorldTick>));
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Skill {
+    Farming,
+    Cooking,
+    Construction,
+    Hauling,
+}
+
+impl Skill {
+    pub const ALL: [Skill; 4] = [Skill::Farming, Skill::Cooking, Skill::Construction, Skill::Hauling];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Skill::Farming => "Farming",
+            Skill::Cooking => "Cooking",
+            Skill::Construction => "Construction",
+            Skill::Hauling => "Hauling",
+        }
+    }
+
+    // Skill practised by operating a workstation that makes `good`
+    pub fn for_good(good: Good) -> Skill {
+        match good {
+            Good::Food => Skill::Cooking,
+            Good::Grain => Skill::Farming,
+            Good::Wood => Skill::Construction,
+        }
+    }
+}
+
+#[derive(Resource, Debug, Clone, Copy)]
+pub struct SkillConfig {
+    pub max_level: f32,
+    // Level gained per tick of practice
+    pub gain_per_tick: f32,
+    // Unused ticks before a skill starts to decay
+    pub decay_grace_ticks: u32,
+    pub decay_per_tick: f32,
+    // Work speed bonus per level, 0.1 = +10% per level
+    pub speed_per_level: f32,
+    // Chance per level that a finished item yields one extra
+    pub quality_per_level: f32,
+    // Job score bonus per level when agents pick jobs
+    pub assignment_weight: f32,
+}
+
+impl Default for SkillConfig {
+    fn default() -> Self {
+        Self {
+            max_level: 10.0,
+            gain_per_tick: 0.02,
+            decay_grace_ticks: 100,
+            decay_per_tick: 0.002,
+            speed_per_level: 0.1,
+            quality_per_level: 0.02,
+            assignment_weight: 0.5,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SkillTrack {
+    pub level: f32,
+    pub unused_ticks: u32,
+}
+
+#[derive(Component, Debug, Clone, Default)]
+pub struct Skills(pub HashMap<Skill, SkillTrack>);
+
+impl Skills {
+    pub fn random(rng: &mut impl Rng) -> Self {
+        Self(Skill::ALL.iter().map(|s| (*s, SkillTrack { level: rng.random_range(0.0..=2.0), unused_ticks: 0 })).collect())
+    }
+
+    pub fn level(&self, skill: Skill) -> f32 {
+        self.0.get(&skill).map_or(0.0, |t| t.level)
+    }
+
+    pub fn speed_multiplier(&self, skill: Skill, config: &SkillConfig) -> f32 {
+        1.0 + self.level(skill) * config.speed_per_level
+    }
+}
+
+fn roll_starting_skills(
+    mut commands: Commands,
+    new_characters: Query<Entity, (Added<Health>, Without<Skills>)>,
+) {
+    let mut rng = rand::rng();
+    for character in &new_characters {
+        commands.entity(character).insert(Skills::random(&mut rng));
+    }
+}
+
+// The skill of the agent's current job improves; every other track idles and eventually decays
+fn practice_skills(
+    config: Res<SkillConfig>,
+    board: Res<JobBoard>,
+    mut agents: Query<(&mut Skills, Option<&CurrentJob>)>,
+) {
+    for (mut skills, job) in &mut agents {
+        let practised = job
+            .and_then(|j| board.get(j.id))
+            .and_then(|j| j.required_skill)
+            .map(|(skill, _)| skill);
+        for skill in Skill::ALL {
+            let track = skills.0.entry(skill).or_default();
+            if Some(skill) == practised {
+                track.level = (track.level + config.gain_per_tick).min(config.max_level);
+                track.unused_ticks = 0;
+            } else {
+                track.unused_ticks = track.unused_ticks.saturating_add(1);
+                if track.unused_ticks > config.decay_grace_ticks {
+                    track.level = (track.level - config.decay_per_tick).max(0.0);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,105 @@
+pub struct SpoilageSystems;
+use crate::*;
+use crate::building::BuildingKind;
+use crate::ownership::{Holdings, Owner};
+use crate::production::{Bussiness, Good, Operational, Storage};
+use crate::storage::GroundStockpile;
+use bevy::prelude::*;
+use bevy::ecs::schedule::common_conditions::on_event;
+
+impl Plugin for SpoilageSystems {
+    fn build(&self, app: &mut App) {
+        app
+            .insert_resource(SpoilageConfig::default())
+            .add_event::<GoodsSpoiled>()
+            .add_systems(Update, spoil_goods.run_if(on_event::<WorldTick>));
+    }
+}
+
+#[derive(Resource, Debug, Clone, Copy)]
+pub struct SpoilageConfig {
+    // Decay speed of goods lying on ground stockpiles
+    pub ground_multiplier: f32,
+}
+
+impl Default for SpoilageConfig {
+    fn default() -> Self {
+        Self { ground_multiplier: 1.5 }
+    }
+}
+
+impl Good {
+    // Ticks a unit lasts in ordinary storage; None for goods that keep forever
+    pub(crate) fn shelf_life(&self) -> Option<f32> {
+        match self {
+            Good::Food => Some(240.0),
+            Good::Grain => Some(2000.0),
+            Good::Wood => None,
+        }
+    }
+}
+
+impl BuildingKind {
+    // Decay speed of perishables held by an owner of this building, 1.0 being no protection
+    pub fn preservation(&self) -> f32 {
+        match self {
+            BuildingKind::Cellar => 0.5,
+            BuildingKind::ColdStore => 0.2,
+            _ => 1.0,
+        }
+    }
+}
+
+#[derive(Event, Debug, Clone, Copy)]
+pub struct GoodsSpoiled {
+    // Business the lost goods belonged to, if any
+    pub owner: Option<Entity>,
+    pub good: Good,
+    pub amount: i32,
+}
+
+// Ages every perishable batch by one tick, slowed by the best cellar or cold store the owner runs
+fn spoil_goods(
+    config: Res<SpoilageConfig>,
+    mut storages: Query<(Entity, &mut Storage, Option<&Owner>, Option<&Holdings>, Has<Bussiness>, Has<GroundStockpile>)>,
+    preservers: Query<&BuildingKind, With<Operational>>,
+    holdings: Query<&Holdings>,
+    mut spoiled: EventWriter<GoodsSpoiled>,
+) {
+    let best_preservation = |holdings: Option<&Holdings>| {
+        holdings
+            .into_iter()
+            .flat_map(|h| h.iter())
+            .filter_map(|e| preservers.get(e).ok())
+            .map(|kind| kind.preservation())
+            .fold(1.0f32, f32::min)
+    };
+
+    for (entity, mut storage, owner, own_holdings, is_business, on_ground) in &mut storages {
+        if storage.batches.is_empty() { continue; }
+        let business = if is_business { Some(entity) } else { owner.map(|o| o.0) };
+        let rate = if on_ground {
+            config.ground_multiplier
+        } else if is_business {
+            best_preservation(own_holdings)
+        } else {
+            best_preservation(business.and_then(|b| holdings.get(b).ok()))
+        };
+
+        let storage = &mut *storage;
+        for (good, queue) in storage.batches.iter_mut() {
+            let mut lost = 0;
+            for batch in queue.iter_mut() {
+                batch.freshness -= rate;
+            }
+            // Oldest batches sit at the front and go off first
+            while queue.front().is_some_and(|b| b.freshness <= 0.0) {
+                lost += queue.pop_front().map_or(0, |b| b.amount);
+            }
+            if lost > 0 {
+                *storage.goods.entry(*good).or_default() -= lost;
+                spoiled.write(GoodsSpoiled { owner: business, good: *good, amount: lost });
+            }
+        }
+    }
+}
@@ -0,0 +1,200 @@
+pub struct StorageSystems;
+use crate::*;
+use crate::ownership::Owner;
+use crate::production::{Batch, Bussiness, Good, Storage, Workstation};
+use bevy::prelude::*;
+use bevy::ecs::schedule::common_conditions::on_event;
+use std::collections::HashMap;
+
+impl Plugin for StorageSystems {
+    fn build(&self, app: &mut App) {
+        app
+            .insert_resource(StorageConfig::default())
+            .add_systems(Update, (equip_storage_capacity, show_output_blocked))
+            .add_systems(Update, clear_empty_stockpiles.run_if(on_event::<WorldTick>));
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum GoodCategory {
+    Foodstuff,
+    Material,
+}
+
+impl Good {
+    pub(crate) fn category(&self) -> GoodCategory {
+        match self {
+            Good::Food | Good::Grain => GoodCategory::Foodstuff,
+            Good::Wood => GoodCategory::Material,
+        }
+    }
+
+    // Space a single unit takes up in storage
+    pub(crate) fn volume(&self) -> f32 {
+        match self {
+            Good::Food => 1.0,
+            Good::Grain => 0.5,
+            Good::Wood => 2.0,
+        }
+    }
+}
+
+// Limits on what a `Storage` can hold. Storages without one are unbounded.
+#[derive(Component, Debug, Clone)]
+pub struct StorageCapacity {
+    pub max_volume: f32,
+    pub max_weight: f32,
+    // Unit limits for single categories, on top of volume and weight
+    pub per_category: HashMap<GoodCategory, i32>,
+}
+
+impl StorageCapacity {
+    pub fn new(max_volume: f32, max_weight: f32) -> Self {
+        Self { max_volume, max_weight, per_category: HashMap::new() }
+    }
+
+    pub fn with_category_limit(mut self, category: GoodCategory, units: i32) -> Self {
+        self.per_category.insert(category, units);
+        self
+    }
+
+    // Units of `good` that still fit into `storage`
+    pub fn room_for(&self, storage: &Storage, good: Good) -> i32 {
+        let (volume, weight) = storage.goods.iter().fold((0.0, 0.0), |(v, w), (g, n)| {
+            (v + g.volume() * *n as f32, w + g.weight() * *n as f32)
+        });
+        let by_volume = ((self.max_volume - volume) / good.volume()).floor() as i32;
+        let by_weight = ((self.max_weight - weight) / good.weight()).floor() as i32;
+        let by_category = self.per_category.get(&good.category()).map_or(i32::MAX, |limit| {
+            let held: i32 = storage.goods.iter().filter(|(g, _)| g.category() == good.category()).map(|(_, n)| n).sum();
+            limit - held
+        });
+        by_volume.min(by_weight).min(by_category).max(0)
+    }
+}
+
+// What a workstation does when its output storage is full
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OverflowPolicy {
+    // Stop finishing items until there is room again
+    #[default]
+    Halt,
+    // Keep producing and put what does not fit on a ground stockpile nearby
+    Spill,
+}
+
+#[derive(Component, Debug, Clone, Copy)]
+pub struct Overflow(pub OverflowPolicy);
+
+// Set on workstations whose last finished items did not fit into their output storage
+#[derive(Component)]
+pub struct OutputBlocked;
+
+// Goods lying on the ground, waiting to be hauled to the owner's drop-off
+#[derive(Component)]
+pub struct GroundStockpile;
+
+#[derive(Resource, Debug, Clone)]
+pub struct StorageConfig {
+    pub workstation_buffer: StorageCapacity,
+    pub warehouse: StorageCapacity,
+    pub overflow: OverflowPolicy,
+    // Spilled goods join an existing stockpile within this distance
+    pub stockpile_radius: f32,
+}
+
+impl Default for StorageConfig {
+    fn default() -> Self {
+        Self {
+            workstation_buffer: StorageCapacity::new(20.0, 30.0),
+            warehouse: StorageCapacity::new(400.0, 600.0).with_category_limit(GoodCategory::Material, 150),
+            overflow: OverflowPolicy::Halt,
+            stockpile_radius: 40.0,
+        }
+    }
+}
+
+// Puts `batches` of `good` on the ground at `pos`, onto a stockpile of `owner` within reach or a new one
+pub(crate) fn spill_to_stockpile(
+    commands: &mut Commands,
+    config: &StorageConfig,
+    stockpiles: &Query<(Entity, &Owner, &GlobalTransform), With<GroundStockpile>>,
+    storages: &mut Query<&mut Storage>,
+    owner: Entity,
+    pos: Vec2,
+    good: Good,
+    batches: Vec<Batch>,
+) {
+    let nearby = stockpiles
+        .iter()
+        .filter(|(_, o, t)| o.0 == owner && t.translation().truncate().distance(pos) <= config.stockpile_radius)
+        .find_map(|(e, _, _)| storages.get_mut(e).ok());
+    if let Some(mut storage) = nearby {
+        storage.add_batches(good, batches);
+        return;
+    }
+    let mut storage = Storage::default();
+    storage.add_batches(good, batches);
+    commands.spawn((
+        EntityLabel("Stockpile".to_string()),
+        GroundStockpile,
+        Owner(owner),
+        storage,
+        Sprite::from_color(Color::srgb(0.55, 0.4, 0.2), Vec2::splat(8.0)),
+        Transform::from_xyz(pos.x, pos.y - 20.0, 0.2),
+    ));
+}
+
+fn equip_storage_capacity(
+    mut commands: Commands,
+    config: Res<StorageConfig>,
+    workstations: Query<Entity, (Added<Workstation>, Without<StorageCapacity>)>,
+    businesses: Query<Entity, (Added<Bussiness>, Without<StorageCapacity>)>,
+) {
+    for workstation in &workstations {
+        commands.entity(workstation).insert((config.workstation_buffer.clone(), Overflow(config.overflow)));
+    }
+    // A business' stock is what its warehouses hold
+    for business in &businesses {
+        commands.entity(business).insert(config.warehouse.clone());
+    }
+}
+
+fn clear_empty_stockpiles(
+    mut commands: Commands,
+    stockpiles: Query<(Entity, &Storage), With<GroundStockpile>>,
+) {
+    for (entity, storage) in &stockpiles {
+        if storage.goods.values().all(|n| *n <= 0) {
+            commands.entity(entity).despawn();
+        }
+    }
+}
+
+#[derive(Component)]
+struct BlockedMarker;
+
+// A red square over every workstation with nowhere to put its output
+fn show_output_blocked(
+    mut commands: Commands,
+    blocked: Query<Entity, Added<OutputBlocked>>,
+    mut unblocked: RemovedComponents<OutputBlocked>,
+    markers: Query<(Entity, &ChildOf), With<BlockedMarker>>,
+) {
+    for workstation in &blocked {
+        commands.entity(workstation).with_children(|parent| {
+            parent.spawn((
+                BlockedMarker,
+                Sprite::from_color(Color::srgb(0.9, 0.1, 0.1), Vec2::splat(6.0)),
+                Transform::from_xyz(0.0, 12.0, 0.5),
+            ));
+        });
+    }
+    for workstation in unblocked.read() {
+        for (marker, parent) in &markers {
+            if parent.parent() == workstation {
+                commands.entity(marker).despawn();
+            }
+        }
+    }
+}
@@ -1,13 +1,39 @@
 use bevy::prelude::{Resource, Vec2};
 
+pub const TERRAIN_WATER: u8 = 2;
+pub const TERRAIN_FARM: u8 = 3;
+
 #[derive(Clone, Copy, Debug, Default)]
 pub struct Tile {
     pub terrain_type: u8,
+    // Growth multiplier for crops planted here
+    pub fertility: f32,
+}
+
+// Planning designation of a tile, kept apart from its terrain
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Zone {
+    #[default]
+    Unzoned,
+    Residential,
+    Commercial,
+    Industrial,
+}
+
+impl Zone {
+    pub const ALL: [Zone; 4] = [Zone::Unzoned, Zone::Residential, Zone::Commercial, Zone::Industrial];
 }
 
 #[derive(Clone, Debug, Default, Resource)]
 pub struct WorldGrid {
     tiles: Vec<Tile>,
+    zones: Vec<Zone>,
+    // Bumped on every zone change so overlays know when to redraw
+    zone_revision: u64,
+    // Indices of tiles whose terrain changed, for views that redraw incrementally
+    terrain_log: Vec<u32>,
+    // Absolute position of `terrain_log[0]`, entries before it have been trimmed
+    terrain_log_start: usize,
     scale: u16,
     width: u32,
     height: u32,
@@ -15,12 +41,36 @@ pub struct WorldGrid {
 
 impl WorldGrid {
     pub fn new(height: u32, width: u32, scale: u16) -> WorldGrid {
-        Self {
-            tiles: vec![Tile::default(); (height * width) as usize],
+        // Gentle bands of richer and poorer soil, 0.5..1.5
+        let tiles = (0..height * width)
+            .map(|i| {
+                let (x, y) = ((i % width) as f32, (i / width) as f32);
+                let fertility = 1.0 + 0.5 * (x * 0.07).sin() * (y * 0.05).cos();
+                Tile { terrain_type: 0, fertility }
+            })
+            .collect();
+        let mut grid = Self {
+            tiles,
+            zones: vec![Zone::Unzoned; (height * width) as usize],
+            zone_revision: 0,
+            terrain_log: Vec::new(),
+            terrain_log_start: 0,
             scale,
             width,
             height,
+        };
+        // A pond just south of the starting workshops, clear of them, and two further out,
+        // as fractions of the grid and a radius in cells
+        for (fx, fy, radius) in [(0.5, 0.44, 3.0), (0.3, 0.7, 6.0), (0.72, 0.28, 5.0)] {
+            let centre = Vec2::new(width as f32 * fx, height as f32 * fy);
+            for idx in 0..grid.tiles.len() {
+                let cell = Vec2::new((idx as u32 % width) as f32, (idx as u32 / width) as f32);
+                if cell.distance(centre) <= radius {
+                    grid.tiles[idx].terrain_type = TERRAIN_WATER;
+                }
+            }
         }
+        grid
     }
 
     pub fn width(&self) -> u32 {
@@ -62,32 +112,122 @@ impl WorldGrid {
     }
 
     pub fn modify_rectangle(&mut self, origin: Vec2, size: Vec2) {
-        let width = size.x.round() as i32;
-        let height = size.y.round() as i32;
-        if width <= 0 || height <= 0 {
-            return;
+        self.set_rectangle(origin, size, 1);
+    }
+
+    // Frees a footprint claimed by `modify_rectangle`
+    pub fn clear_rectangle(&mut self, origin: Vec2, size: Vec2) {
+        self.set_rectangle(origin, size, 0);
+    }
+
+    fn set_rectangle(&mut self, origin: Vec2, size: Vec2, terrain_type: u8) {
+        for coords in Self::rectangle_cells(origin, size) {
+            if let Some(idx) = self.vec2_to_index(coords) {
+                if let Some(tile) = self.tiles.get_mut(idx) {
+                    tile.terrain_type = terrain_type;
+                }
+                self.log_terrain_change(idx);
+            }
+        }
+    }
+
+    fn log_terrain_change(&mut self, idx: usize) {
+        const MAX_LOG: usize = 1 << 16;
+        self.terrain_log.push(idx as u32);
+        // Readers that fall this far behind redraw everything instead
+        if self.terrain_log.len() > MAX_LOG {
+            self.terrain_log.drain(..MAX_LOG / 2);
+            self.terrain_log_start += MAX_LOG / 2;
         }
+    }
+
+    // Position after the newest terrain change, to pass to `terrain_changes_since` later
+    pub fn terrain_log_end(&self) -> usize {
+        self.terrain_log_start + self.terrain_log.len()
+    }
+
+    // Cells changed since `position`, or None if that part of the log is gone
+    pub fn terrain_changes_since(&self, position: usize) -> Option<impl Iterator<Item = Vec2> + '_> {
+        let from = position.checked_sub(self.terrain_log_start)?;
+        let width = self.width;
+        Some(self.terrain_log.get(from..)?.iter().map(move |i| Vec2::new((i % width) as f32, (i / width) as f32)))
+    }
+
+    // True if the whole footprint is inside the grid and on unclaimed terrain
+    pub fn is_area_free(&self, origin: Vec2, size: Vec2) -> bool {
+        Self::rectangle_cells(origin, size).all(|coords| {
+            self.vec2_to_index(coords)
+                .and_then(|idx| self.tiles.get(idx))
+                .is_some_and(|tile| tile.terrain_type == 0)
+        })
+    }
+
+    // Cells covered by a building of `size` centred on the `origin` cell, as used by placement
+    fn rectangle_cells(origin: Vec2, size: Vec2) -> impl Iterator<Item = Vec2> {
+        let w = size.x.round() as i32;
+        let h = size.y.round() as i32;
 
         let cx = origin.x.floor() as i32;
         let cy = origin.y.floor() as i32;
-        let w = width;
-        let h = height;
 
         let start_x = cx - (w - 1) / 2;
-        let end_x = start_x + w - 1;
         let start_y = cy - (h - 1) / 2;
-        let end_y = start_y + h - 1;
-
-        for y in start_y..=end_y {
-            for x in start_x..=end_x {
-                let coords = Vec2::new(x as f32, y as f32);
-                if let Some(idx) = self.vec2_to_index(coords) {
-                    if let Some(tile) = self.tiles.get_mut(idx) {
-                        tile.terrain_type = 1;
-                    }
+
+        // Non-positive sizes yield no cells
+        (start_y..start_y + h.max(0))
+            .flat_map(move |y| (start_x..start_x + w.max(0)).map(move |x| Vec2::new(x as f32, y as f32)))
+    }
+
+    pub fn tile(&self, coords: Vec2) -> Option<&Tile> {
+        self.tiles.get(self.vec2_to_index(coords)?)
+    }
+
+    pub fn set_terrain(&mut self, coords: Vec2, terrain_type: u8) {
+        if let Some(idx) = self.vec2_to_index(coords) {
+            self.tiles[idx].terrain_type = terrain_type;
+            self.log_terrain_change(idx);
+        }
+    }
+
+    pub fn zone(&self, coords: Vec2) -> Zone {
+        self.vec2_to_index(coords).map_or(Zone::Unzoned, |idx| self.zones[idx])
+    }
+
+    // Zones all cells between two opposite corners, both included
+    pub fn set_zone_rect(&mut self, a: Vec2, b: Vec2, zone: Zone) {
+        let (min, max) = (a.min(b), a.max(b));
+        for y in min.y as i32..=max.y as i32 {
+            for x in min.x as i32..=max.x as i32 {
+                if let Some(idx) = self.vec2_to_index(Vec2::new(x as f32, y as f32)) {
+                    self.zones[idx] = zone;
                 }
             }
         }
+        self.zone_revision += 1;
+    }
+
+    pub fn zone_revision(&self) -> u64 {
+        self.zone_revision
+    }
+
+    // Zones under a building of `size` on `origin`, cells outside the grid count as unzoned
+    pub fn footprint_zones(&self, origin: Vec2, size: Vec2) -> impl Iterator<Item = Zone> + '_ {
+        Self::rectangle_cells(origin, size).map(|coords| self.zone(coords))
+    }
+
+    // Cells carrying `zone`, row by row
+    pub fn cells_in_zone(&self, zone: Zone) -> impl Iterator<Item = Vec2> + '_ {
+        let width = self.width;
+        self.zones
+            .iter()
+            .enumerate()
+            .filter(move |(_, z)| **z == zone)
+            .map(move |(i, _)| Vec2::new((i as u32 % width) as f32, (i as u32 / width) as f32))
+    }
+
+    pub fn tile_at_world(&self, world: Vec2) -> Option<&Tile> {
+        let idx = self.vec2_to_index(self.world_to_grid(world))?;
+        self.tiles.get(idx)
     }
 
     fn vec2_to_index(&self, coords: Vec2) -> Option<usize> {
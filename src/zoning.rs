@@ -0,0 +1,271 @@
+pub struct ZoningSystems;
+use crate::*;
+use crate::building::{spawn_building, BuildTool, BuildingKind, BuildingPlaced, ConstructionSite, Footprint};
+use crate::notifications::Notification;
+use crate::ownership::{Factions, Owner};
+use crate::population::{House, Residents};
+use crate::world_grid::Zone;
+use bevy::prelude::*;
+use bevy::ecs::schedule::common_conditions::on_event;
+use bevy::image::ImageSampler;
+use bevy::render::render_asset::RenderAssetUsages;
+use bevy::render::render_resource::{Extent3d, TextureDimension, TextureFormat};
+use std::collections::HashMap;
+
+impl Plugin for ZoningSystems {
+    fn build(&self, app: &mut App) {
+        app
+            .insert_resource(ZoneRules::default())
+            .insert_resource(ZoningConfig::default())
+            .insert_resource(GrowthTicks(0))
+            .init_resource::<ZoneDrag>()
+            .add_systems(Startup, spawn_zone_overlay)
+            .add_systems(Update, (redraw_zone_overlay, show_zone_overlay))
+            .add_systems(Update, paint_zones.run_if(in_state(GameControlState::Building)))
+            .add_systems(Update, grow_residential_zones.run_if(on_event::<WorldTick>));
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ZoneRule {
+    // Buildings the player may place on the zone
+    pub placeable: Vec<BuildingKind>,
+    // Buildings that spring up there on their own: AI expansion and town housing
+    pub grows: Vec<BuildingKind>,
+    pub color: Color,
+}
+
+#[derive(Resource, Debug, Clone)]
+pub struct ZoneRules(pub HashMap<Zone, ZoneRule>);
+
+impl Default for ZoneRules {
+    fn default() -> Self {
+        use BuildingKind::*;
+        Self(HashMap::from([
+            // Unplanned land takes anything but never grows housing by itself
+            (Zone::Unzoned, ZoneRule { placeable: vec![House, Workshop, Cellar, ColdStore], grows: vec![Workshop], color: Color::NONE }),
+            (Zone::Residential, ZoneRule { placeable: vec![House, Cellar], grows: vec![House], color: Color::srgb(0.2, 0.8, 0.2) }),
+            (Zone::Commercial, ZoneRule { placeable: vec![Workshop, Cellar, ColdStore], grows: vec![Workshop], color: Color::srgb(0.2, 0.4, 0.9) }),
+            (Zone::Industrial, ZoneRule { placeable: vec![Workshop, ColdStore], grows: vec![Workshop], color: Color::srgb(0.9, 0.7, 0.1) }),
+        ]))
+    }
+}
+
+impl ZoneRules {
+    fn rule(&self, zone: Zone) -> Option<&ZoneRule> {
+        self.0.get(&zone)
+    }
+
+    // True if every cell under the footprint lets the player place `kind`
+    pub fn allows_placement(&self, grid: &WorldGrid, origin: Vec2, size: Vec2, kind: BuildingKind) -> bool {
+        grid.footprint_zones(origin, size).all(|z| self.rule(z).is_some_and(|r| r.placeable.contains(&kind)))
+    }
+
+    // True if every cell under the footprint lets `kind` grow without the player
+    pub fn allows_growth(&self, grid: &WorldGrid, origin: Vec2, size: Vec2, kind: BuildingKind) -> bool {
+        grid.footprint_zones(origin, size).all(|z| self.rule(z).is_some_and(|r| r.grows.contains(&kind)))
+    }
+
+    // Where newcomers look for a home: residential zones first, then anywhere houses are allowed
+    pub fn housing_rank(&self, zone: Zone) -> Option<u8> {
+        if !self.rule(zone).is_some_and(|r| r.placeable.contains(&BuildingKind::House)) { return None; }
+        Some(if zone == Zone::Residential { 0 } else { 1 })
+    }
+}
+
+#[derive(Resource, Debug, Clone, Copy)]
+pub struct ZoningConfig {
+    pub overlay_alpha: f32,
+    // The town builds a house every this many ticks while all beds are taken
+    pub growth_interval_ticks: u32,
+    pub site_attempts: u32,
+}
+
+impl Default for ZoningConfig {
+    fn default() -> Self {
+        Self {
+            overlay_alpha: 0.3,
+            growth_interval_ticks: 50,
+            site_attempts: 32,
+        }
+    }
+}
+
+#[derive(Resource)]
+struct GrowthTicks(u32);
+
+// Random free site where `kind` may grow, trying zones meant for it before anything else
+pub fn find_zoned_site(grid: &WorldGrid, rules: &ZoneRules, kind: BuildingKind, attempts: u32) -> Option<Vec2> {
+    let mut rng = rand::rng();
+    let size = kind.size();
+    let fits = |origin: &Vec2| grid.is_area_free(*origin, size) && rules.allows_growth(grid, *origin, size, kind);
+    let zoned: Vec<Vec2> = Zone::ALL
+        .into_iter()
+        .filter(|z| *z != Zone::Unzoned && rules.rule(*z).is_some_and(|r| r.grows.contains(&kind)))
+        .flat_map(|z| grid.cells_in_zone(z))
+        .collect();
+    let in_zone = (0..attempts)
+        .filter(|_| !zoned.is_empty())
+        .map(|_| zoned[rng.random_range(0..zoned.len())])
+        .find(&fits);
+    in_zone.or_else(|| {
+        (0..attempts)
+            .map(|_| Vec2::new(rng.random_range(0..grid.width()) as f32, rng.random_range(0..grid.height()) as f32))
+            .find(&fits)
+    })
+}
+
+// Corner where a zone drag started, while the button is held
+#[derive(Resource, Default)]
+struct ZoneDrag(Option<Vec2>);
+
+// Dragging with a zone tool marks the rectangle between press and release
+fn paint_zones(
+    actions: Res<Actions>,
+    state: Res<BuildingControlState>,
+    over_ui: Res<UiBlockHoverCount>,
+    rules: Res<ZoneRules>,
+    mut drag: ResMut<ZoneDrag>,
+    mut grid: ResMut<WorldGrid>,
+    mut gizmos: Gizmos,
+    mut notifications: EventWriter<Notification>,
+) {
+    let BuildTool::Zone(zone) = state.tool else {
+        drag.0 = None;
+        return;
+    };
+    if actions.just_pressed(Action::Place) && over_ui.0 == 0 {
+        drag.0 = Some(state.cur_cel);
+    }
+    let Some(start) = drag.0 else { return; };
+
+    if actions.just_released(Action::Place) {
+        grid.set_zone_rect(start, state.cur_cel, zone);
+        drag.0 = None;
+        notifications.write(Notification::info("Zoning", format!("Zoned {:?} to {:?} as {:?}", start, state.cur_cel, zone)));
+        return;
+    }
+    let scale = grid.scale() as f32;
+    let (min, max) = (start.min(state.cur_cel), start.max(state.cur_cel));
+    let corner = grid.grid_to_world(min, Vec2::ONE) - Vec2::splat(scale / 2.0);
+    let size = (max - min + Vec2::ONE) * scale;
+    let color = rules.rule(zone).map_or(Color::WHITE, |r| r.color);
+    gizmos.rect_2d(corner + size / 2.0, size, if zone == Zone::Unzoned { Color::WHITE } else { color });
+}
+
+// One pixel per tile, stretched over the whole grid
+#[derive(Component)]
+struct ZoneOverlay {
+    revision: Option<u64>,
+}
+
+fn spawn_zone_overlay(
+    mut commands: Commands,
+    mut images: ResMut<Assets<Image>>,
+    grid: Res<WorldGrid>,
+) {
+    let mut image = Image::new_fill(
+        Extent3d { width: grid.width(), height: grid.height(), depth_or_array_layers: 1 },
+        TextureDimension::D2,
+        &[0, 0, 0, 0],
+        TextureFormat::Rgba8UnormSrgb,
+        RenderAssetUsages::default(),
+    );
+    image.sampler = ImageSampler::nearest();
+    let scale = grid.scale() as f32;
+    commands.spawn((
+        Name::new("Zone overlay"),
+        ZoneOverlay { revision: None },
+        Sprite {
+            image: images.add(image),
+            custom_size: Some(Vec2::new(grid.width() as f32 * scale, grid.height() as f32 * scale)),
+            ..default()
+        },
+        Transform::from_xyz(0.0, 0.0, 0.02),
+        Visibility::Hidden,
+        Pickable::IGNORE,
+    ));
+}
+
+fn redraw_zone_overlay(
+    grid: Res<WorldGrid>,
+    rules: Res<ZoneRules>,
+    config: Res<ZoningConfig>,
+    mut images: ResMut<Assets<Image>>,
+    mut overlays: Query<(&mut ZoneOverlay, &Sprite)>,
+) {
+    let Ok((mut overlay, sprite)) = overlays.single_mut() else { return; };
+    if overlay.revision == Some(grid.zone_revision()) { return; }
+    let Some(data) = images.get_mut(&sprite.image).and_then(|image| image.data.as_mut()) else { return; };
+
+    let colors: HashMap<Zone, [u8; 4]> = Zone::ALL
+        .into_iter()
+        .map(|z| {
+            let color = rules.rule(z).map_or(Color::NONE, |r| r.color);
+            let alpha = if color.alpha() > 0.0 { config.overlay_alpha } else { 0.0 };
+            (z, color.with_alpha(alpha).to_srgba().to_u8_array())
+        })
+        .collect();
+    let (width, height) = (grid.width(), grid.height());
+    for y in 0..height {
+        for x in 0..width {
+            // Image rows run top-down, grid rows bottom-up
+            let pixel = (((height - 1 - y) * width + x) * 4) as usize;
+            let zone = grid.zone(Vec2::new(x as f32, y as f32));
+            data[pixel..pixel + 4].copy_from_slice(&colors[&zone]);
+        }
+    }
+    overlay.revision = Some(grid.zone_revision());
+}
+
+// Zones are always shown while building, elsewhere on request
+fn show_zone_overlay(
+    actions: Res<Actions>,
+    state: Res<State<GameControlState>>,
+    mut shown: Local<bool>,
+    mut overlays: Query<&mut Visibility, With<ZoneOverlay>>,
+) {
+    if actions.just_pressed(Action::ToggleZoneOverlay) {
+        *shown = !*shown;
+    }
+    let visible = *shown || *state.get() == GameControlState::Building;
+    for mut visibility in &mut overlays {
+        let wanted = if visible { Visibility::Inherited } else { Visibility::Hidden };
+        if *visibility != wanted {
+            *visibility = wanted;
+        }
+    }
+}
+
+// While every bed is taken the town puts up a house on residential land
+fn grow_residential_zones(
+    mut commands: Commands,
+    mut ticks: ResMut<GrowthTicks>,
+    config: Res<ZoningConfig>,
+    rules: Res<ZoneRules>,
+    factions: Res<Factions>,
+    mut grid: ResMut<WorldGrid>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    common_materials: Res<CommonMaterials>,
+    houses: Query<(&House, Option<&Residents>)>,
+    mut placed: EventWriter<BuildingPlaced>,
+    mut notifications: EventWriter<Notification>,
+) {
+    ticks.0 += 1;
+    if ticks.0 % config.growth_interval_ticks.max(1) != 0 { return; }
+    let free_beds: u32 = houses
+        .iter()
+        .map(|(house, residents)| house.capacity.saturating_sub(residents.map_or(0, |r| r.count()) as u32))
+        .sum();
+    if free_beds > 0 { return; }
+
+    let kind = BuildingKind::House;
+    let size = kind.size();
+    let Some(origin) = find_zoned_site(&grid, &rules, kind, config.site_attempts) else { return; };
+    let pos = grid.grid_to_world(origin, size);
+    let building = spawn_building(&mut commands, &mut meshes, &grid, common_materials.building.clone(), size, pos);
+    commands.entity(building).insert((kind, ConstructionSite::for_size(size), Footprint { origin, size }, Owner(factions.world)));
+    grid.modify_rectangle(origin, size);
+    placed.write(BuildingPlaced { entity: building, kind, origin, size });
+    notifications.write(Notification::info("Zoning", "The town started building a house").with_target(building));
+}